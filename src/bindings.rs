@@ -0,0 +1,563 @@
+//! A [`Universe`](../universe/struct.Universe.html) exported to JavaScript as a class of the same
+//! name, so a UI can be built around the simulation in any JS framework.
+//!
+//! ```js
+//! const universe = new wasm.Universe(800, 600);
+//! universe.initRandom();
+//! universe.subscribe(event => console.log(event.type, event.id));
+//! const id = universe.addBody(100, 100, 0, 0.5, 1e7, 8);
+//! universe.tick(10);
+//! const positions = universe.positions(); // Float64Array of x, y pairs
+//! universe.draw(canvas.getContext('2d'));
+//!
+//! universe.startRecording({ format: 'gif', width: 400, height: 300, every: 2 });
+//! universe.tick(100);
+//! const gif = universe.stopRecording(); // Uint8Array
+//! ```
+use js_sys::{Function, Object, Reflect};
+use uuid::Uuid;
+use wasm_bindgen::prelude::*;
+
+use super::*;
+
+/// A [`Universe`](../universe/struct.Universe.html) owned by JavaScript.
+///
+/// Bodies are listed as typed arrays in the order of their ids from [`ids`](#method.ids), with
+/// two entries per body for vectors.
+#[wasm_bindgen(js_name = Universe)]
+pub struct JsUniverse {
+    universe: Universe,
+    listeners: Vec<Function>,
+    recorder: Option<Recorder>,
+}
+
+#[wasm_bindgen(js_class = Universe)]
+impl JsUniverse {
+    /// Create an empty universe filling a canvas of `width` by `height` pixels.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: f64, height: f64) -> Self {
+        Self::from(Universe::new((width, height)))
+    }
+
+    /// Create the solar system, drawn onto a canvas of `width` by `height` pixels.
+    #[wasm_bindgen(js_name = solarSystem)]
+    pub fn solar_system(width: f64, height: f64, with_moons: bool) -> Self {
+        Self::from(scenario::solar_system((width, height), with_moons))
+    }
+
+    /// Create a disk galaxy, drawn onto a canvas of `width` by `height` pixels. With `tracers`,
+    /// the disk is made of `bodies` massless tracers orbiting the bulge and halo, otherwise of
+    /// `bodies` planets pulling on each other.
+    pub fn galaxy(width: f64, height: f64, bodies: usize, tracers: bool) -> Self {
+        let cfg = scenario::GalaxyConfig {
+            bodies,
+            tracers,
+            ..scenario::GalaxyConfig::default()
+        };
+        Self::from(scenario::galaxy((width, height), &cfg))
+    }
+
+    /// Add a sun and a bunch of random planets.
+    #[wasm_bindgen(js_name = initRandom)]
+    pub fn init_random(&mut self) {
+        self.universe.init_random();
+        self.dispatch();
+    }
+
+    /// Add a body and return its id.
+    #[wasm_bindgen(js_name = addBody)]
+    pub fn add_body(&mut self, x: f64, y: f64, vx: f64, vy: f64, mass: f64, radius: f64) -> String {
+        let planet = Planet::with_mass(Point::new(x, y), mass, radius, Point::new(vx, vy));
+        let id = self.universe.add(planet);
+        self.dispatch();
+        id.to_string()
+    }
+
+    /// Remove the body with the given id. Returns whether there was such a body.
+    #[wasm_bindgen(js_name = removeBody)]
+    pub fn remove_body(&mut self, id: &str) -> bool {
+        let removed = match Uuid::parse_str(id) {
+            Ok(id) => self.universe.remove(id),
+            Err(_) => false,
+        };
+        self.dispatch();
+        removed
+    }
+
+    #[wasm_bindgen(js_name = bodyCount)]
+    pub fn body_count(&self) -> usize {
+        self.universe.planets().len()
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.map(|p| p.id().to_string())
+    }
+
+    pub fn positions(&self) -> Vec<f64> {
+        self.flat_map(|p| p.pos())
+    }
+
+    pub fn velocities(&self) -> Vec<f64> {
+        self.flat_map(<Planet as Newtonian>::velocity)
+    }
+
+    pub fn masses(&self) -> Vec<f64> {
+        self.map(|p| p.mass())
+    }
+
+    pub fn radii(&self) -> Vec<f64> {
+        self.map(|p| p.radius())
+    }
+
+    /// Release a massless test particle, pulled by the bodies without pulling back.
+    #[wasm_bindgen(js_name = addParticle)]
+    pub fn add_particle(&mut self, x: f64, y: f64, vx: f64, vy: f64) {
+        self.universe
+            .add_particle(Point::new(x, y), Point::new(vx, vy));
+    }
+
+    /// Release test particles on a grid `spacing` apart across the whole universe, on circular
+    /// orbits if `orbiting`. Returns the number of particles released.
+    #[wasm_bindgen(js_name = seedParticles)]
+    pub fn seed_particles(&mut self, spacing: f64, orbiting: bool) -> usize {
+        let (width, height) = self.universe.dimensions();
+        let area = Rect::new(width / 2.0, height / 2.0, width, height);
+        self.universe.seed_particles(&area, spacing, orbiting)
+    }
+
+    #[wasm_bindgen(js_name = clearParticles)]
+    pub fn clear_particles(&mut self) {
+        self.universe.clear_particles();
+    }
+
+    #[wasm_bindgen(js_name = particleCount)]
+    pub fn particle_count(&self) -> usize {
+        self.universe.particles().len()
+    }
+
+    /// Positions of all test particles as x, y pairs.
+    #[wasm_bindgen(js_name = particlePositions)]
+    pub fn particle_positions(&self) -> Vec<f64> {
+        flatten(self.universe.particles().positions())
+    }
+
+    /// Velocities of all test particles as x, y pairs, in the order of their positions.
+    #[wasm_bindgen(js_name = particleVelocities)]
+    pub fn particle_velocities(&self) -> Vec<f64> {
+        flatten(self.universe.particles().velocities())
+    }
+
+    /// Time elapsed since the universe began.
+    pub fn time(&self) -> f64 {
+        self.universe.time()
+    }
+
+    /// The current settings as a plain object with the keys `dt`, `theta`, `softening`,
+    /// `trailLength`, `trailFade`, `colorBy`, `colorMap`, `showQuadTree`, `showVelocities`,
+    /// `velocityScale`, `showAccelerations`, `accelerationScale`, `showField`, `fieldQuantity`,
+    /// `fieldStyle`, `fieldResolution`, `fieldColorMap`, `fieldContours`, `absorbParticles`,
+    /// `streamlineLength`, `boundary`, `solver`, `integrator` and `collisions`. Depending on the
+    /// collision model, there's also `restitution`, or `fragmentThreshold` and `fragmentPieces`.
+    pub fn config(&self) -> Object {
+        let universe = &self.universe;
+        let trails = universe.trail_config();
+        let style = universe.style();
+
+        let config = Object::new();
+        let set = |key: &str, value: JsValue| {
+            Reflect::set(&config, &key.into(), &value).unwrap();
+        };
+        set("dt", universe.dt().into());
+        set("theta", universe.theta().into());
+        set("softening", universe.softening().into());
+        set("trailLength", (trails.length as f64).into());
+        set("trailFade", trails.fade.into());
+        set("colorBy", style.color_by.name().into());
+        set("colorMap", style.map.name().into());
+        set("showQuadTree", universe.show_quad_tree().into());
+        let overlays = universe.overlays();
+        set("showVelocities", overlays.velocities.into());
+        set("velocityScale", overlays.velocity_scale.into());
+        set("showAccelerations", overlays.accelerations.into());
+        set("accelerationScale", overlays.acceleration_scale.into());
+        let field = universe.field();
+        set("showField", field.show.into());
+        set("fieldQuantity", field.quantity.name().into());
+        set("fieldStyle", field.style.name().into());
+        set("fieldResolution", field.resolution.into());
+        set("fieldColorMap", field.map.name().into());
+        set("fieldContours", (field.contours as f64).into());
+        let particles = universe.particle_config();
+        set("absorbParticles", particles.absorb.into());
+        set("streamlineLength", (particles.streamlines as f64).into());
+        set("boundary", universe.boundary().name().into());
+        set("solver", universe.solver().name().into());
+        set("integrator", universe.integrator().name().into());
+        let collisions = universe.collisions();
+        set("collisions", collisions.name().into());
+        match collisions {
+            CollisionModel::Merge => {}
+            CollisionModel::Elastic { restitution } | CollisionModel::Inelastic { restitution } => {
+                set("restitution", restitution.into())
+            }
+            CollisionModel::Fragment { threshold, pieces } => {
+                set("fragmentThreshold", threshold.into());
+                set("fragmentPieces", (pieces as f64).into());
+            }
+        }
+        config
+    }
+
+    /// Change settings, taking an object with any of the keys of [`config`](#method.config).
+    /// Missing keys are left as they are. All keys are checked before any of them is applied, so
+    /// a single invalid one leaves every setting as it was. Numbers need to be finite, `dt`
+    /// positive and `theta` at least 0.
+    #[wasm_bindgen(js_name = setConfig)]
+    pub fn set_config(&mut self, config: &Object) -> Result<(), JsValue> {
+        let get = |key: &str| {
+            Reflect::get(config, &key.into())
+                .ok()
+                .filter(|v| !v.is_undefined())
+        };
+        let number = |key: &str| -> Result<Option<f64>, JsValue> {
+            get(key)
+                .map(|v| {
+                    v.as_f64()
+                        .filter(|n| n.is_finite())
+                        .ok_or_else(|| invalid(key))
+                })
+                .transpose()
+        };
+        let string = |key: &str| get(key).and_then(|v| v.as_string());
+        let flag = |key: &str| get(key).and_then(|v| v.as_bool());
+
+        let universe = &self.universe;
+        let dt = number("dt")?.unwrap_or_else(|| universe.dt());
+        if dt <= 0.0 {
+            return Err(invalid("dt"));
+        }
+        let theta = number("theta")?.unwrap_or_else(|| universe.theta());
+        if theta < 0.0 {
+            return Err(invalid("theta"));
+        }
+        let softening = number("softening")?.map_or_else(|| universe.softening(), |s| s.max(0.0));
+
+        let mut trails = universe.trail_config();
+        if let Some(length) = number("trailLength")? {
+            trails.length = length.max(0.0) as usize;
+        }
+        if let Some(fade) = number("trailFade")? {
+            trails.fade = fade;
+        }
+
+        let mut style = universe.style();
+        if let Some(color_by) = parse(string("colorBy"))? {
+            style.color_by = color_by;
+        }
+        if let Some(map) = parse(string("colorMap"))? {
+            style.map = map;
+        }
+
+        let show_quad_tree = flag("showQuadTree").unwrap_or_else(|| universe.show_quad_tree());
+        let mut overlays = universe.overlays();
+        if let Some(show) = flag("showVelocities") {
+            overlays.velocities = show;
+        }
+        if let Some(scale) = number("velocityScale")? {
+            overlays.velocity_scale = scale;
+        }
+        if let Some(show) = flag("showAccelerations") {
+            overlays.accelerations = show;
+        }
+        if let Some(scale) = number("accelerationScale")? {
+            overlays.acceleration_scale = scale;
+        }
+
+        let mut field = universe.field();
+        if let Some(show) = flag("showField") {
+            field.show = show;
+        }
+        if let Some(quantity) = parse(string("fieldQuantity"))? {
+            field.quantity = quantity;
+        }
+        if let Some(style) = parse(string("fieldStyle"))? {
+            field.style = style;
+        }
+        if let Some(resolution) = number("fieldResolution")? {
+            field.resolution = resolution;
+        }
+        if let Some(map) = parse(string("fieldColorMap"))? {
+            field.map = map;
+        }
+        if let Some(contours) = number("fieldContours")? {
+            field.contours = contours.max(0.0) as usize;
+        }
+
+        let mut particles = universe.particle_config();
+        if let Some(absorb) = flag("absorbParticles") {
+            particles.absorb = absorb;
+        }
+        if let Some(length) = number("streamlineLength")? {
+            particles.streamlines = length.max(0.0) as usize;
+        }
+
+        let boundary = parse(string("boundary"))?.unwrap_or_else(|| universe.boundary());
+        let solver = parse(string("solver"))?.unwrap_or_else(|| universe.solver());
+        let integrator = parse(string("integrator"))?.unwrap_or_else(|| universe.integrator());
+        let mut collisions = parse(string("collisions"))?.unwrap_or_else(|| universe.collisions());
+        match &mut collisions {
+            CollisionModel::Merge => {}
+            CollisionModel::Elastic { restitution } | CollisionModel::Inelastic { restitution } => {
+                if let Some(r) = number("restitution")? {
+                    *restitution = r;
+                }
+            }
+            CollisionModel::Fragment { threshold, pieces } => {
+                if let Some(t) = number("fragmentThreshold")? {
+                    *threshold = t;
+                }
+                if let Some(n) = number("fragmentPieces")? {
+                    *pieces = n.max(0.0) as usize;
+                }
+            }
+        }
+
+        let universe = &mut self.universe;
+        universe.set_dt(dt);
+        universe.set_theta(theta);
+        universe.set_softening(softening);
+        universe.set_trail_config(trails);
+        universe.set_style(style);
+        universe.set_show_quad_tree(show_quad_tree);
+        universe.set_overlays(overlays);
+        universe.set_field(field);
+        universe.set_particle_config(particles);
+        universe.set_boundary(boundary);
+        universe.set_solver(solver);
+        universe.set_integrator(integrator);
+        universe.set_collisions(collisions);
+        Ok(())
+    }
+
+    /// Advance the universe by `n` ticks, recording them if
+    /// [`startRecording`](#method.start_recording) was called.
+    pub fn tick(&mut self, n: u32) -> Result<(), JsValue> {
+        for _ in 0..n {
+            self.universe.tick();
+            if let Some(recorder) = &mut self.recorder {
+                recorder
+                    .capture(&self.universe)
+                    .map_err(|e| JsValue::from(e.as_str()))?;
+            }
+        }
+        self.dispatch();
+        Ok(())
+    }
+
+    /// Start recording the following ticks as an animation, configured by an object with any of
+    /// the keys `format` (`gif` or `apng`), `width` and `height` in pixels, `every` to record only
+    /// every so many ticks, `fps`, `palette` (`adaptive` or `web-safe`) and `colors`. By default,
+    /// every tick is recorded into a GIF at the size of the canvas. Drops any recording in
+    /// progress.
+    #[wasm_bindgen(js_name = startRecording)]
+    pub fn start_recording(&mut self, config: &Object) -> Result<(), JsValue> {
+        let get = |key: &str| {
+            Reflect::get(config, &key.into())
+                .ok()
+                .filter(|v| !v.is_undefined())
+        };
+        let number = |key: &str| -> Result<Option<f64>, JsValue> {
+            get(key)
+                .map(|v| v.as_f64().ok_or_else(|| invalid(key)))
+                .transpose()
+        };
+        let string = |key: &str| get(key).and_then(|v| v.as_string());
+
+        let format = match string("format") {
+            Some(format) => format
+                .parse()
+                .map_err(|e: String| JsValue::from(e.as_str()))?,
+            None => AnimationFormat::Gif,
+        };
+        let (width, height) = self.universe.camera().viewport();
+        let width = number("width")?.unwrap_or(width).max(0.0).round() as usize;
+        let height = number("height")?.unwrap_or(height).max(0.0).round() as usize;
+        let mut cfg = AnimationConfig::new(format, width, height);
+        if let Some(every) = number("every")? {
+            cfg.every = every.max(0.0) as usize;
+        }
+        if let Some(fps) = number("fps")? {
+            cfg.fps = fps;
+        }
+        if let Some(palette) = string("palette") {
+            cfg.palette = palette
+                .parse()
+                .map_err(|e: String| JsValue::from(e.as_str()))?;
+        }
+        if let (Some(colors), Quantization::Adaptive { .. }) = (number("colors")?, cfg.palette) {
+            cfg.palette = Quantization::Adaptive {
+                colors: colors.max(0.0) as usize,
+            };
+        }
+
+        let recorder = Recorder::new(cfg).map_err(|e| JsValue::from(e.as_str()))?;
+        self.recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Whether ticks are being recorded.
+    #[wasm_bindgen(js_name = isRecording)]
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Stop recording and return the animation as a `Uint8Array`, ready to be put into a `Blob`.
+    /// Fails if nothing was recorded.
+    #[wasm_bindgen(js_name = stopRecording)]
+    pub fn stop_recording(&mut self) -> Result<Vec<u8>, JsValue> {
+        let recorder = self
+            .recorder
+            .take()
+            .ok_or_else(|| JsValue::from("Not recording"))?;
+        recorder.finish().map_err(|e| JsValue::from(e.as_str()))
+    }
+
+    /// Draw the universe onto a canvas.
+    pub fn draw(&self, ctx: &web_sys::CanvasRenderingContext2d) {
+        self.universe.draw(ctx);
+    }
+
+    /// Draw the universe into an SVG document, with trails, the quad tree and velocities as set
+    /// in the [`config`](#method.config). The same state always gives the same document.
+    #[wasm_bindgen(js_name = toSvg)]
+    pub fn to_svg(&self) -> String {
+        self.universe.to_svg()
+    }
+
+    /// Save the complete state as text, see [`Snapshot`](../snapshot/struct.Snapshot.html).
+    pub fn snapshot(&self) -> String {
+        self.universe.snapshot().to_string()
+    }
+
+    /// Restore a state saved by [`snapshot`](#method.snapshot).
+    pub fn restore(&mut self, snapshot: &str) -> Result<(), JsValue> {
+        let snapshot: Snapshot = snapshot
+            .parse()
+            .map_err(|e: String| JsValue::from(e.as_str()))?;
+        self.universe.restore(&snapshot);
+        Ok(())
+    }
+
+    /// Call `listener` with every event, as an object with a `type` like `spawn` and the fields
+    /// of the [`Event`](../event/enum.Event.html).
+    pub fn subscribe(&mut self, listener: Function) {
+        self.listeners.push(listener);
+    }
+}
+
+impl JsUniverse {
+    pub fn universe(&self) -> &Universe {
+        &self.universe
+    }
+
+    pub fn universe_mut(&mut self) -> &mut Universe {
+        &mut self.universe
+    }
+
+    fn map<T>(&self, f: impl Fn(&Planet) -> T) -> Vec<T> {
+        self.universe
+            .planets()
+            .iter()
+            .map(|p| f(&p.borrow()))
+            .collect()
+    }
+
+    fn flat_map(&self, f: impl Fn(&Planet) -> Point) -> Vec<f64> {
+        self.universe
+            .planets()
+            .iter()
+            .flat_map(|p| {
+                let v = f(&p.borrow());
+                vec![v.x, v.y]
+            })
+            .collect()
+    }
+
+    fn dispatch(&mut self) {
+        dispatch(&mut self.universe, &self.listeners);
+    }
+}
+
+impl From<Universe> for JsUniverse {
+    fn from(universe: Universe) -> Self {
+        Self {
+            universe,
+            listeners: vec![],
+            recorder: None,
+        }
+    }
+}
+
+/// Flatten vectors into x, y pairs.
+fn flatten(points: &[Point]) -> Vec<f64> {
+    points.iter().flat_map(|p| [p.x, p.y]).collect()
+}
+
+/// Hand all pending events of `universe` to the listeners. Without listeners, events are dropped.
+pub fn dispatch(universe: &mut Universe, listeners: &[Function]) {
+    let events: Vec<Event> = universe.drain_events().collect();
+    for event in events {
+        let event = event_to_js(&event);
+        for listener in listeners {
+            // A failing listener shouldn't keep the others from hearing about it.
+            let _ = listener.call1(&JsValue::NULL, &event);
+        }
+    }
+}
+
+fn invalid(key: &str) -> JsValue {
+    JsValue::from(format!("Invalid value for {}", key))
+}
+
+/// Parse the name of a setting, if there is one.
+fn parse<T: std::str::FromStr<Err = String>>(name: Option<String>) -> Result<Option<T>, JsValue> {
+    name.map(|name| name.parse().map_err(|e: String| JsValue::from(e.as_str())))
+        .transpose()
+}
+
+/// Convert an [`Event`](../event/enum.Event.html) into a plain JS object.
+pub fn event_to_js(event: &Event) -> JsValue {
+    let object = Object::new();
+    let set = |key: &str, value: JsValue| {
+        Reflect::set(&object, &key.into(), &value).unwrap();
+    };
+    set("type", event.kind().into());
+    set("id", event.id().to_string().into());
+    set("time", event.time().into());
+    match event {
+        Event::Spawn { pos, mass, .. } => {
+            set("x", pos.x.into());
+            set("y", pos.y.into());
+            set("mass", (*mass).into());
+        }
+        Event::Remove { .. } => {}
+        Event::Merge {
+            eaten,
+            mass,
+            eaten_mass,
+            pos,
+            ..
+        } => {
+            set("eaten", eaten.to_string().into());
+            set("mass", (*mass).into());
+            set("eatenMass", (*eaten_mass).into());
+            set("x", pos.x.into());
+            set("y", pos.y.into());
+        }
+        Event::Escape { pos, .. } => {
+            set("x", pos.x.into());
+            set("y", pos.y.into());
+        }
+    }
+    object.into()
+}
@@ -0,0 +1,30 @@
+/// What happens to planets reaching the edges of the [`Universe`](../universe/struct.Universe.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Boundary {
+    /// They re-enter the universe on the opposite side.
+    Wrap,
+    /// They escape and are removed from the universe.
+    Open,
+}
+
+impl Boundary {
+    /// How the boundary is written in snapshots and the JavaScript config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Boundary::Wrap => "wrap",
+            Boundary::Open => "open",
+        }
+    }
+}
+
+impl std::str::FromStr for Boundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(Boundary::Wrap),
+            "open" => Ok(Boundary::Open),
+            _ => Err(format!("Unknown boundary: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use uuid::Uuid;
+
+use super::*;
+
+/// Maps world coordinates of the [`Universe`](./universe/struct.Universe.html) onto the pixels of
+/// the canvas they are drawn to.
+///
+/// The camera can be panned, zoomed and rotated, or made to follow a planet, keeping it in the
+/// middle of the viewport.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// The world coordinates shown in the middle of the viewport.
+    center: Point,
+    /// Pixels per world length unit.
+    scale: f64,
+    /// Counter-clockwise rotation of the world on screen, in radians.
+    rotation: f64,
+    /// Width and height of the viewport in pixels.
+    viewport: (f64, f64),
+    /// Id of the planet kept in the middle of the viewport.
+    follow: Option<Uuid>,
+}
+
+impl Camera {
+    pub fn new(center: Point, scale: f64, viewport: (f64, f64)) -> Self {
+        Self {
+            center,
+            scale,
+            rotation: 0.0,
+            viewport,
+            follow: None,
+        }
+    }
+
+    /// Create a `Camera` that fits the world rectangle from (0, 0) to `dimensions` into the
+    /// viewport.
+    pub fn fit(dimensions: (f64, f64), viewport: (f64, f64)) -> Self {
+        let scale = (viewport.0 / dimensions.0).min(viewport.1 / dimensions.1);
+        let center = Point::new(dimensions.0 / 2.0, dimensions.1 / 2.0);
+        Self::new(center, scale, viewport)
+    }
+
+    pub fn world_to_screen(&self, p: Point) -> Point {
+        (p - self.center).rotate(-self.rotation) * self.scale + self.viewport_center()
+    }
+
+    pub fn screen_to_world(&self, p: Point) -> Point {
+        ((p - self.viewport_center()) / self.scale).rotate(self.rotation) + self.center
+    }
+
+    /// Convert a length in world units into pixels.
+    pub fn to_pixels(&self, l: f64) -> f64 {
+        l * self.scale
+    }
+
+    /// Move the view by a distance given in pixels. Stops following any planet.
+    pub fn pan(&mut self, delta: Point) {
+        self.follow = None;
+        self.center -= (delta / self.scale).rotate(self.rotation);
+    }
+
+    /// Zoom by `factor`, keeping the world point under the screen position `anchor` in place.
+    pub fn zoom_at(&mut self, anchor: Point, factor: f64) {
+        let world_anchor = self.screen_to_world(anchor);
+        self.scale *= factor;
+        // While following, the followed planet stays centered instead.
+        if self.follow.is_none() {
+            self.center += world_anchor - self.screen_to_world(anchor);
+        }
+    }
+
+    /// Rotate the view by `angle` radians around the middle of the viewport.
+    pub fn rotate(&mut self, angle: f64) {
+        self.rotation += angle;
+    }
+
+    pub fn set_center(&mut self, center: Point) {
+        self.center = center;
+    }
+
+    pub fn set_viewport(&mut self, viewport: (f64, f64)) {
+        self.viewport = viewport;
+    }
+
+    /// Keep the planet with the given id in the middle of the viewport, or stop doing so.
+    pub fn set_follow(&mut self, id: Option<Uuid>) {
+        self.follow = id;
+    }
+
+    pub fn follow(&self) -> Option<Uuid> {
+        self.follow
+    }
+
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    pub fn viewport(&self) -> (f64, f64) {
+        self.viewport
+    }
+
+    fn viewport_center(&self) -> Point {
+        Point::new(self.viewport.0 / 2.0, self.viewport.1 / 2.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn assert_close(a: Point, b: Point) {
+        assert!(a.distance_to(b) < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn basics() {
+        let cam = Camera::fit((2.0, 1.0), (800.0, 600.0));
+        assert_eq!(cam.scale(), 400.0);
+        assert_eq!(
+            cam.world_to_screen(Point::new(1.0, 0.5)),
+            Point::new(400.0, 300.0)
+        );
+        assert_eq!(
+            cam.world_to_screen(Point::new(0.0, 0.0)),
+            Point::new(0.0, 100.0)
+        );
+        assert_eq!(
+            cam.screen_to_world(Point::new(0.0, 100.0)),
+            Point::new(0.0, 0.0)
+        );
+        assert_eq!(cam.to_pixels(0.25), 100.0);
+    }
+
+    #[test]
+    fn pan_zoom_rotate() {
+        let mut cam = Camera::fit((800.0, 600.0), (800.0, 600.0));
+
+        cam.pan(Point::new(10.0, -20.0));
+        assert_close(cam.center(), Point::new(390.0, 320.0));
+
+        let anchor = Point::new(100.0, 100.0);
+        let world = cam.screen_to_world(anchor);
+        cam.zoom_at(anchor, 2.0);
+        assert_eq!(cam.scale(), 2.0);
+        assert_close(cam.world_to_screen(world), anchor);
+
+        cam.rotate(PI / 2.0);
+        for p in &[Point::new(0.0, 0.0), Point::new(123.0, 456.0)] {
+            assert_close(cam.screen_to_world(cam.world_to_screen(*p)), *p);
+        }
+        // A quarter turn to the left moves what was right of center to the top.
+        let right = cam.center() + Point::new(10.0, 0.0);
+        assert_close(cam.world_to_screen(right), Point::new(400.0, 280.0));
+    }
+}
@@ -0,0 +1,419 @@
+//! What happens when two planets touch.
+//!
+//! Two planets collide as soon as the distance between them is no more than the sum of their
+//! radii. The configured [`CollisionModel`](./enum.CollisionModel.html) then decides whether
+//! they merge, bounce off each other or shatter.
+//!
+//! Since planets jump from one position to the next on each tick, fast ones could pass right
+//! through each other without ever touching at the end of a tick. So collisions are also looked
+//! for along the way, see [`time_of_impact`](./fn.time_of_impact.html).
+use std::f64::consts::PI;
+use uuid::Uuid;
+
+use super::*;
+
+/// How colliding planets are resolved.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum CollisionModel {
+    /// The planets merge into one, conserving mass and momentum.
+    #[default]
+    Merge,
+    /// The planets bounce off each other. A `restitution` of 1 keeps all kinetic energy of the
+    /// impact, 0 none of it.
+    Elastic { restitution: f64 },
+    /// The planets bounce like [`Elastic`](#variant.Elastic), but stick together and merge if
+    /// they're too slow to escape each other's gravity afterwards.
+    Inelastic { restitution: f64 },
+    /// Gentle impacts merge the planets, while those with a specific impact energy above
+    /// `threshold` shatter them. The larger one survives as a remnant, the rest of the mass
+    /// flies off as `pieces` pieces of debris.
+    ///
+    /// The specific impact energy is the kinetic energy of the planets' relative motion per unit
+    /// of their combined mass, measured in squared length units per squared time unit.
+    Fragment { threshold: f64, pieces: usize },
+}
+
+impl CollisionModel {
+    /// The name of the model alone. Its parameters are stored next to it, and parsing the name
+    /// gives the model with default ones.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollisionModel::Merge => "merge",
+            CollisionModel::Elastic { .. } => "elastic",
+            CollisionModel::Inelastic { .. } => "inelastic",
+            CollisionModel::Fragment { .. } => "fragment",
+        }
+    }
+}
+
+impl std::str::FromStr for CollisionModel {
+    type Err = String;
+
+    /// Parse a model by name, with default parameters.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "merge" => Ok(CollisionModel::Merge),
+            "elastic" => Ok(CollisionModel::Elastic { restitution: 1.0 }),
+            "inelastic" => Ok(CollisionModel::Inelastic { restitution: 0.5 }),
+            "fragment" => Ok(CollisionModel::Fragment {
+                threshold: 0.05,
+                pieces: 6,
+            }),
+            _ => Err(format!("Unknown collision model: {}", s)),
+        }
+    }
+}
+
+/// What became of two colliding planets.
+#[derive(Clone)]
+pub enum Outcome {
+    /// The planets bounced off each other.
+    Bounced,
+    /// `eater` swallowed `eaten`, which is now dead. Masses and position are from before the
+    /// collision. Any `debris` that flew off is left to be added to the universe.
+    Merged {
+        eater: Uuid,
+        eaten: Uuid,
+        mass: f64,
+        eaten_mass: f64,
+        pos: Point,
+        debris: Vec<Planet>,
+    },
+}
+
+/// Whether two planets touch.
+pub fn overlap(a: &Planet, b: &Planet) -> bool {
+    a.pos().distance_to(b.pos()) <= a.radius() + b.radius()
+}
+
+/// Find the point in time, as a fraction between 0 and 1 of a tick, at which two bodies moving
+/// in straight lines from `a_from` to `a_to` and `b_from` to `b_to` first come within `distance`
+/// of each other. Bodies already that close at the start meet at 0 if they're closing in, and
+/// not at all if they're moving apart.
+pub fn time_of_impact(
+    a_from: Point,
+    a_to: Point,
+    b_from: Point,
+    b_to: Point,
+    distance: f64,
+) -> Option<f64> {
+    // Look at it from a's point of view, with b moving relative to it.
+    let start = b_from - a_from;
+    let motion = (b_to - a_to) - start;
+
+    let c = dot(start, start) - distance * distance;
+    if c <= 0.0 {
+        return if dot(start, motion) < 0.0 {
+            Some(0.0)
+        } else {
+            None
+        };
+    }
+    let a = dot(motion, motion);
+    let b = 2.0 * dot(start, motion);
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if (0.0..=1.0).contains(&t) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Resolve the collision of two touching planets according to `model`, given the gravitational
+/// constant `g`.
+pub fn resolve(model: CollisionModel, a: &Planet, b: &Planet, g: f64) -> Outcome {
+    match model {
+        CollisionModel::Merge => merge(a, b),
+        CollisionModel::Elastic { restitution } => {
+            bounce(a, b, restitution);
+            Outcome::Bounced
+        }
+        CollisionModel::Inelastic { restitution } => {
+            bounce(a, b, restitution);
+            let d = a.pos().distance_to(b.pos());
+            let escape_speed = (2.0 * g * (a.mass() + b.mass()) / d).sqrt();
+            if relative_velocity(a, b).mag() < escape_speed {
+                merge(a, b)
+            } else {
+                Outcome::Bounced
+            }
+        }
+        CollisionModel::Fragment { threshold, pieces } => {
+            if pieces < 2 || a.pinned() || b.pinned() {
+                return merge(a, b);
+            }
+            let (ma, mb) = (a.mass(), b.mass());
+            let reduced_mass = ma * mb / (ma + mb);
+            let v = relative_velocity(a, b).mag();
+            let energy = 0.5 * reduced_mass * v * v / (ma + mb);
+            if energy <= threshold {
+                merge(a, b)
+            } else {
+                fragment(a, b, energy / threshold, pieces, g)
+            }
+        }
+    }
+}
+
+fn relative_velocity(a: &Planet, b: &Planet) -> Point {
+    Newtonian::velocity(b) - Newtonian::velocity(a)
+}
+
+/// The planet doing the eating: one that's pinned, otherwise the heavier one.
+fn eater_first<'a>(a: &'a Planet, b: &'a Planet) -> (&'a Planet, &'a Planet) {
+    if b.pinned() && !a.pinned() || (a.pinned() == b.pinned() && b.mass() > a.mass()) {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
+
+fn merge(a: &Planet, b: &Planet) -> Outcome {
+    let (eater, eaten) = eater_first(a, b);
+    let outcome = Outcome::Merged {
+        eater: eater.id(),
+        eaten: eaten.id(),
+        mass: eater.mass(),
+        eaten_mass: eaten.mass(),
+        pos: eater.pos(),
+        debris: vec![],
+    };
+    eater.eat(eaten);
+    eaten.die();
+    outcome
+}
+
+/// Exchange momentum along the line between the planets' centers and push them apart so they
+/// don't overlap anymore. Pinned planets act as if they were infinitely heavy.
+fn bounce(a: &Planet, b: &Planet, restitution: f64) {
+    let inverse_mass = |p: &Planet| if p.pinned() { 0.0 } else { 1.0 / p.mass() };
+    let (wa, wb) = (inverse_mass(a), inverse_mass(b));
+    if wa + wb == 0.0 {
+        return;
+    }
+
+    let direction = b.pos() - a.pos();
+    let d = direction.mag();
+    let normal = if d > 0.0 {
+        direction / d
+    } else {
+        Point::new(1.0, 0.0)
+    };
+
+    // Only planets moving towards each other bounce, those already separating are left alone.
+    let approach = dot(relative_velocity(a, b), normal);
+    if approach < 0.0 {
+        let impulse = -(1.0 + restitution) * approach / (wa + wb);
+        a.set_velocity(Newtonian::velocity(a) - normal * (impulse * wa));
+        b.set_velocity(Newtonian::velocity(b) + normal * (impulse * wb));
+    }
+
+    let overlap = a.radius() + b.radius() - d;
+    if overlap > 0.0 {
+        a.set_pos(a.pos() - normal * (overlap * wa / (wa + wb)));
+        b.set_pos(b.pos() + normal * (overlap * wb / (wa + wb)));
+    }
+}
+
+/// Shatter the planets. The eater keeps a share of the combined mass that shrinks the more the
+/// impact energy exceeds the threshold by `excess`, the rest is split evenly into `pieces`
+/// pieces of debris flying off in a ring around it. Mass and momentum are conserved.
+fn fragment(a: &Planet, b: &Planet, excess: f64, pieces: usize, g: f64) -> Outcome {
+    let v_rel = relative_velocity(a, b).mag();
+    let density = (a.density() + b.density()) / 2.0;
+    let (remnant, _) = eater_first(a, b);
+    let mut outcome = merge(a, b);
+
+    let total = remnant.mass();
+    let remnant_mass = total * (0.5 / excess).max(0.1);
+    remnant.set_mass(remnant_mass);
+
+    let piece_mass = (total - remnant_mass) / pieces as f64;
+    let piece_radius = (3.0 * piece_mass / (4.0 * PI * density)).cbrt();
+    let distance = remnant.radius() + 2.0 * piece_radius;
+    // Fast enough to get away from the remnant instead of falling right back onto it.
+    let escape_speed = (2.0 * g * remnant_mass / distance).sqrt();
+    let speed = (0.5 * v_rel).max(1.2 * escape_speed);
+
+    // Evenly spread directions cancel out, so the debris adds no net momentum.
+    let pieces = (0..pieces).map(|i| {
+        let angle = 2.0 * PI * i as f64 / pieces as f64;
+        let direction = Point::new(angle.cos(), angle.sin());
+        let pos = remnant.pos() + direction * distance;
+        let velocity = Newtonian::velocity(remnant) + direction * speed;
+        Planet::new(pos.x, pos.y, density, piece_radius, velocity)
+    });
+    if let Outcome::Merged { debris, .. } = &mut outcome {
+        debris.extend(pieces);
+    }
+    outcome
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::close;
+
+    fn momentum(planets: &[&Planet]) -> Point {
+        planets.iter().fold(Point::default(), |acc, p| {
+            acc + Newtonian::velocity(*p) * p.mass()
+        })
+    }
+
+    fn pair() -> (Planet, Planet) {
+        let a = Planet::new(0.0, 0.0, 1000.0, 2.0, Point::new(1.0, 0.5));
+        let b = Planet::new(3.0, 0.0, 1000.0, 1.0, Point::new(-2.0, 0.0));
+        (a, b)
+    }
+
+    #[test]
+    fn swept() {
+        let still = Point::new(0.0, 0.0);
+        // Passing right through within a single tick.
+        let t = time_of_impact(
+            still,
+            still,
+            Point::new(-10.0, 0.0),
+            Point::new(10.0, 0.0),
+            2.0,
+        );
+        assert!(close(t.unwrap(), 0.4, 1e-9));
+        // Missing by a hair.
+        let t = time_of_impact(
+            still,
+            still,
+            Point::new(-10.0, 2.1),
+            Point::new(10.0, 2.1),
+            2.0,
+        );
+        assert_eq!(t, None);
+        // Not getting there in time.
+        let t = time_of_impact(
+            still,
+            still,
+            Point::new(-10.0, 0.0),
+            Point::new(-5.0, 0.0),
+            2.0,
+        );
+        assert_eq!(t, None);
+        // Touching from the start and closing in.
+        let t = time_of_impact(
+            still,
+            still,
+            Point::new(1.5, 0.0),
+            Point::new(-9.0, 0.0),
+            2.0,
+        );
+        assert_eq!(t, Some(0.0));
+        // Touching from the start, but moving apart.
+        let t = time_of_impact(
+            still,
+            still,
+            Point::new(1.0, 0.0),
+            Point::new(9.0, 0.0),
+            2.0,
+        );
+        assert_eq!(t, None);
+    }
+
+    #[test]
+    fn merge() {
+        let (a, b) = pair();
+        assert!(overlap(&a, &b));
+        let (mass, p) = (a.mass() + b.mass(), momentum(&[&a, &b]));
+
+        match resolve(CollisionModel::Merge, &a, &b, 1.0) {
+            Outcome::Merged { eater, eaten, .. } => assert_eq!((eater, eaten), (a.id(), b.id())),
+            Outcome::Bounced => panic!("Expected a merge"),
+        }
+        assert!(b.dead());
+        assert!(close(a.mass(), mass, 1e-9));
+        let after = momentum(&[&a]);
+        assert!(close(after.x, p.x, 1e-9) && close(after.y, p.y, 1e-9));
+
+        // Neither mass nor momentum depend on the planets sharing a density.
+        let a = Planet::new(0.0, 0.0, 1000.0, 2.0, Point::new(1.0, 0.5));
+        let b = Planet::new(2.5, 0.0, 8000.0, 0.8, Point::new(-2.0, 0.0));
+        let (mass, p) = (a.mass() + b.mass(), momentum(&[&a, &b]));
+        resolve(CollisionModel::Merge, &a, &b, 1.0);
+        assert!(close(a.mass(), mass, 1e-9));
+        let after = momentum(&[&a]);
+        assert!(close(after.x, p.x, 1e-9) && close(after.y, p.y, 1e-9));
+    }
+
+    #[test]
+    fn bounce() {
+        let (a, b) = pair();
+        let energy = |ps: &[&Planet]| {
+            ps.iter()
+                .map(|p| 0.5 * p.mass() * Newtonian::velocity(*p).mag().powi(2))
+                .sum::<f64>()
+        };
+        let (e, p) = (energy(&[&a, &b]), momentum(&[&a, &b]));
+
+        let model = CollisionModel::Elastic { restitution: 1.0 };
+        assert!(matches!(resolve(model, &a, &b, 1.0), Outcome::Bounced));
+        assert!(close(energy(&[&a, &b]), e, 1e-9));
+        let after = momentum(&[&a, &b]);
+        assert!(close(after.x, p.x, 1e-9) && close(after.y, p.y, 1e-9));
+        assert!(Newtonian::velocity(&b).x > 0.0);
+        assert!(!overlap(&a, &b) || close(a.pos().distance_to(b.pos()), 3.0, 1e-9));
+
+        // Too slow to get away from each other, so they stick together.
+        let (a, b) = pair();
+        let model = CollisionModel::Inelastic { restitution: 0.5 };
+        assert!(matches!(
+            resolve(model, &a, &b, 1e6),
+            Outcome::Merged { .. }
+        ));
+        let (a, b) = pair();
+        assert!(matches!(resolve(model, &a, &b, 1e-9), Outcome::Bounced));
+    }
+
+    #[test]
+    fn fragment() {
+        let (a, b) = pair();
+        let (mass, p) = (a.mass() + b.mass(), momentum(&[&a, &b]));
+        let model = CollisionModel::Fragment {
+            threshold: 0.01,
+            pieces: 5,
+        };
+
+        let debris = match resolve(model, &a, &b, 1e-6) {
+            Outcome::Merged { debris, .. } => debris,
+            Outcome::Bounced => panic!("Expected fragments"),
+        };
+        assert_eq!(debris.len(), 5);
+
+        let mut all: Vec<&Planet> = debris.iter().collect();
+        all.push(&a);
+        let after_mass: f64 = all.iter().map(|p| p.mass()).sum();
+        assert!(close(after_mass, mass, 1e-9));
+        assert!(a.mass() < mass / 2.0);
+        let after = momentum(&all);
+        assert!(close(after.x, p.x, 1e-9) && close(after.y, p.y, 1e-9));
+        for piece in &debris {
+            assert!(!overlap(piece, &a));
+        }
+
+        // Gentle impacts just merge.
+        let (a, b) = pair();
+        let model = CollisionModel::Fragment {
+            threshold: 1e3,
+            pieces: 5,
+        };
+        match resolve(model, &a, &b, 1e-6) {
+            Outcome::Merged { debris, .. } => assert!(debris.is_empty()),
+            Outcome::Bounced => panic!("Expected a merge"),
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use super::*;
+
+/// An RGB colour.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::new(255, 255, 255);
+    pub const GRAY: Color = Color::new(128, 128, 128);
+    pub const BLACK: Color = Color::new(0, 0, 0);
+    pub const MAGENTA: Color = Color::new(255, 0, 255);
+    pub const CYAN: Color = Color::new(0, 255, 255);
+    pub const LIME: Color = Color::new(0, 255, 0);
+    pub const ORANGE: Color = Color::new(255, 165, 0);
+    pub const DODGER_BLUE: Color = Color::new(30, 144, 255);
+    /// The colour of our sun, and of every other sun too.
+    pub const SUN: Color = Color::new(255, 196, 48);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Blend linearly into `other`, with `t` going from 0 to 1.
+    pub fn lerp(&self, other: Color, t: f64) -> Color {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+        )
+    }
+
+    /// Format as a CSS colour, e.g. `#ff00ff`.
+    pub fn to_css(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    /// Parse a CSS hex colour like `#ff00ff`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.trim_start_matches('#');
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .ok_or_else(|| format!("Invalid colour: {}", s))
+        };
+        if hex.len() != 6 {
+            return Err(format!("Invalid colour: {}", s));
+        }
+        Ok(Color::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+/// A gradient mapping values between 0 and 1 onto colours.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ColorMap {
+    /// Perceptually uniform, from dark blue over green to yellow.
+    Viridis,
+    /// From dark red over orange to white, like something glowing.
+    Heat,
+    /// From blue to red.
+    Coolwarm,
+    Grayscale,
+}
+
+impl ColorMap {
+    /// The colour at `t`, clamped to [0, 1].
+    pub fn at(&self, t: f64) -> Color {
+        let stops = self.stops();
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let scaled = t * (stops.len() - 1) as f64;
+        let i = (scaled.floor() as usize).min(stops.len() - 2);
+        stops[i].lerp(stops[i + 1], scaled - i as f64)
+    }
+
+    /// Lower-case name, as stored in `colorMap` and `fieldColorMap` of the JavaScript config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorMap::Viridis => "viridis",
+            ColorMap::Heat => "heat",
+            ColorMap::Coolwarm => "coolwarm",
+            ColorMap::Grayscale => "grayscale",
+        }
+    }
+
+    fn stops(&self) -> &'static [Color] {
+        match self {
+            ColorMap::Viridis => &VIRIDIS,
+            ColorMap::Heat => &HEAT,
+            ColorMap::Coolwarm => &COOLWARM,
+            ColorMap::Grayscale => &GRAYSCALE,
+        }
+    }
+}
+
+const VIRIDIS: [Color; 5] = [
+    Color::new(68, 1, 84),
+    Color::new(59, 82, 139),
+    Color::new(33, 145, 140),
+    Color::new(94, 201, 98),
+    Color::new(253, 231, 37),
+];
+const HEAT: [Color; 4] = [
+    Color::new(96, 0, 0),
+    Color::new(230, 40, 0),
+    Color::new(255, 170, 0),
+    Color::WHITE,
+];
+const COOLWARM: [Color; 3] = [
+    Color::new(59, 76, 192),
+    Color::new(221, 221, 221),
+    Color::new(180, 4, 38),
+];
+const GRAYSCALE: [Color; 2] = [Color::new(64, 64, 64), Color::WHITE];
+
+impl std::str::FromStr for ColorMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "viridis" => Ok(ColorMap::Viridis),
+            "heat" => Ok(ColorMap::Heat),
+            "coolwarm" => Ok(ColorMap::Coolwarm),
+            "grayscale" => Ok(ColorMap::Grayscale),
+            _ => Err(format!("Unknown colour map: {}", s)),
+        }
+    }
+}
+
+/// The property planets are coloured by.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ColorBy {
+    /// Each planet's own colour.
+    Body,
+    Mass,
+    Density,
+    Speed,
+    KineticEnergy,
+    /// Time since the planet was spawned.
+    Age,
+    /// Number of planets merged into this one.
+    Merges,
+}
+
+impl ColorBy {
+    /// The value of the property for the given planet, or `None` when coloring planets by their
+    /// own colour.
+    pub fn value(&self, p: &Planet) -> Option<f64> {
+        let speed = || Newtonian::velocity(p).mag();
+        match self {
+            ColorBy::Body => None,
+            ColorBy::Mass => Some(p.mass()),
+            ColorBy::Density => Some(p.density()),
+            ColorBy::Speed => Some(speed()),
+            ColorBy::KineticEnergy => Some(0.5 * p.mass() * speed() * speed()),
+            ColorBy::Age => Some(p.age()),
+            ColorBy::Merges => Some(p.merges() as f64),
+        }
+    }
+
+    /// Whether values usually span orders of magnitude and are better compared on a log scale.
+    pub fn logarithmic(&self) -> bool {
+        matches!(self, ColorBy::Mass | ColorBy::KineticEnergy)
+    }
+
+    /// Like [`label`](#method.label), but without spaces so it can be parsed back, e.g.
+    /// `kinetic-energy`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorBy::KineticEnergy => "kinetic-energy",
+            _ => self.label(),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorBy::Body => "body",
+            ColorBy::Mass => "mass",
+            ColorBy::Density => "density",
+            ColorBy::Speed => "speed",
+            ColorBy::KineticEnergy => "kinetic energy",
+            ColorBy::Age => "age",
+            ColorBy::Merges => "merges",
+        }
+    }
+}
+
+impl std::str::FromStr for ColorBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "body" => Ok(ColorBy::Body),
+            "mass" => Ok(ColorBy::Mass),
+            "density" => Ok(ColorBy::Density),
+            "speed" => Ok(ColorBy::Speed),
+            "kinetic-energy" => Ok(ColorBy::KineticEnergy),
+            "age" => Ok(ColorBy::Age),
+            "merges" => Ok(ColorBy::Merges),
+            _ => Err(format!("Unknown property: {}", s)),
+        }
+    }
+}
+
+/// How planets are coloured when drawn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StyleConfig {
+    pub color_by: ColorBy,
+    pub map: ColorMap,
+}
+
+impl std::default::Default for StyleConfig {
+    fn default() -> Self {
+        Self {
+            color_by: ColorBy::Body,
+            map: ColorMap::Viridis,
+        }
+    }
+}
+
+/// Maps the values of a property across all planets onto a [`ColorMap`](./enum.ColorMap.html).
+pub struct Palette {
+    cfg: StyleConfig,
+    /// Smallest and largest value of the property, on a log scale if the property has one.
+    range: Option<(f64, f64)>,
+}
+
+impl Palette {
+    /// Find the range of the configured property across `planets`. Suns keep their own colour
+    /// and are left out, since they'd dwarf everything else.
+    pub fn new<'a>(cfg: StyleConfig, planets: impl Iterator<Item = &'a Planet>) -> Self {
+        let mut range: Option<(f64, f64)> = None;
+        for p in planets.filter(|p| !p.is_sun()) {
+            if let Some(v) = cfg.color_by.value(p).map(|v| Self::scale(&cfg, v)) {
+                range = Some(match range {
+                    Some((min, max)) => (min.min(v), max.max(v)),
+                    None => (v, v),
+                });
+            }
+        }
+        Self { cfg, range }
+    }
+
+    /// The colour of a planet.
+    pub fn color(&self, p: &Planet) -> Color {
+        match (self.cfg.color_by.value(p), self.range) {
+            (Some(v), Some((min, max))) if !p.is_sun() => {
+                let t = if max > min {
+                    (Self::scale(&self.cfg, v) - min) / (max - min)
+                } else {
+                    0.5
+                };
+                self.cfg.map.at(t)
+            }
+            _ => p.color().unwrap_or(Color::GRAY),
+        }
+    }
+
+    /// Smallest and largest value of the property, unscaled.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        let unscale = |v: f64| {
+            if self.cfg.color_by.logarithmic() {
+                10_f64.powf(v)
+            } else {
+                v
+            }
+        };
+        self.range.map(|(min, max)| (unscale(min), unscale(max)))
+    }
+
+    pub fn config(&self) -> StyleConfig {
+        self.cfg
+    }
+
+    fn scale(cfg: &StyleConfig, v: f64) -> f64 {
+        if cfg.color_by.logarithmic() {
+            v.max(f64::MIN_POSITIVE).log10()
+        } else {
+            v
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn basics() {
+        assert_eq!(Color::MAGENTA.to_css(), "#ff00ff");
+        assert_eq!("#ff00ff".parse::<Color>(), Ok(Color::MAGENTA));
+        assert!("#ff00f".parse::<Color>().is_err());
+        assert_eq!(
+            Color::BLACK.lerp(Color::WHITE, 0.5),
+            Color::new(128, 128, 128)
+        );
+
+        let map = ColorMap::Grayscale;
+        assert_eq!(map.at(-1.0), Color::new(64, 64, 64));
+        assert_eq!(map.at(2.0), Color::WHITE);
+        assert_eq!(ColorMap::Heat.at(1.0), Color::WHITE);
+
+        for by in &[ColorBy::Body, ColorBy::KineticEnergy, ColorBy::Merges] {
+            assert_eq!(by.name().parse::<ColorBy>(), Ok(*by));
+        }
+        assert_eq!(ColorMap::Coolwarm.name().parse(), Ok(ColorMap::Coolwarm));
+    }
+
+    #[test]
+    fn palette() {
+        let v = Point::new(0.0, 0.0);
+        let small = Planet::new(0.0, 0.0, 1000.0, 1.0, v);
+        let big = Planet::new(0.0, 0.0, 1000.0, 10.0, v);
+        let cfg = StyleConfig {
+            color_by: ColorBy::Mass,
+            map: ColorMap::Grayscale,
+        };
+
+        let palette = Palette::new(cfg, vec![&small, &big].into_iter());
+        assert_eq!(palette.color(&small), Color::new(64, 64, 64));
+        assert_eq!(palette.color(&big), Color::WHITE);
+        let (min, max) = palette.range().unwrap();
+        assert!((min - small.mass()).abs() / small.mass() < 1e-9);
+        assert!((max - big.mass()).abs() / big.mass() < 1e-9);
+
+        let palette = Palette::new(StyleConfig::default(), vec![&small].into_iter());
+        assert_eq!(palette.color(&small), Color::GRAY);
+        let red = Color::new(255, 0, 0);
+        assert_eq!(palette.color(&small.with_color(red)), red);
+    }
+}
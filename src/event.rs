@@ -0,0 +1,65 @@
+use uuid::Uuid;
+
+use super::*;
+
+/// Something that happened in a [`Universe`](../universe/struct.Universe.html).
+///
+/// Events are collected while the universe runs and handed out by
+/// [`Universe::drain_events`](../universe/struct.Universe.html#method.drain_events). Times are
+/// the universe's elapsed time in its own time units. Merges and escapes happening during a tick
+/// are stamped with the time at its end.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A planet was added to the universe.
+    Spawn {
+        id: Uuid,
+        pos: Point,
+        mass: f64,
+        time: f64,
+    },
+    /// A planet was taken out of the universe by hand.
+    Remove { id: Uuid, time: f64 },
+    /// The planet `id` ate the planet `eaten` at `pos`. Masses are from before the merge.
+    Merge {
+        id: Uuid,
+        eaten: Uuid,
+        mass: f64,
+        eaten_mass: f64,
+        pos: Point,
+        time: f64,
+    },
+    /// A planet left the universe through an open [`Boundary`](../boundary/enum.Boundary.html)
+    /// at `pos`.
+    Escape { id: Uuid, pos: Point, time: f64 },
+}
+
+impl Event {
+    /// Name of the kind of event, e.g. `spawn`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::Spawn { .. } => "spawn",
+            Event::Remove { .. } => "remove",
+            Event::Merge { .. } => "merge",
+            Event::Escape { .. } => "escape",
+        }
+    }
+
+    /// Id of the planet the event is about. For merges, that's the one doing the eating.
+    pub fn id(&self) -> Uuid {
+        match self {
+            Event::Spawn { id, .. }
+            | Event::Remove { id, .. }
+            | Event::Merge { id, .. }
+            | Event::Escape { id, .. } => *id,
+        }
+    }
+
+    pub fn time(&self) -> f64 {
+        match self {
+            Event::Spawn { time, .. }
+            | Event::Remove { time, .. }
+            | Event::Merge { time, .. }
+            | Event::Escape { time, .. } => *time,
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Sampling the gravitational field of a [`Universe`](../universe/struct.Universe.html) across
+//! the viewport, to draw wells and the points where pulls cancel out behind the bodies.
+use uuid::Uuid;
+
+use super::*;
+
+/// What of the gravitational field is shown.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FieldQuantity {
+    /// The depth of the potential, showing wells around bodies.
+    Potential,
+    /// The magnitude of the field, i.e. the pull a test mass would feel. Points where pulls
+    /// cancel out, like Lagrange points, show up as dark spots.
+    Strength,
+}
+
+impl FieldQuantity {
+    /// What `--field` takes to show this quantity, and `fieldQuantity` holds in the JavaScript
+    /// config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldQuantity::Potential => "potential",
+            FieldQuantity::Strength => "strength",
+        }
+    }
+}
+
+impl std::str::FromStr for FieldQuantity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "potential" => Ok(FieldQuantity::Potential),
+            "strength" => Ok(FieldQuantity::Strength),
+            _ => Err(format!("Unknown field quantity: {}", s)),
+        }
+    }
+}
+
+/// How the field is drawn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FieldStyle {
+    /// Every cell of the grid filled with the colour of its value.
+    Heatmap,
+    /// Lines of equal value.
+    Contours,
+    /// Contour lines on top of the heatmap.
+    Both,
+}
+
+impl FieldStyle {
+    /// Lower-case name, `both` for contours on the heatmap.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldStyle::Heatmap => "heatmap",
+            FieldStyle::Contours => "contours",
+            FieldStyle::Both => "both",
+        }
+    }
+
+    pub fn heatmap(&self) -> bool {
+        *self != FieldStyle::Contours
+    }
+
+    pub fn contours(&self) -> bool {
+        *self != FieldStyle::Heatmap
+    }
+}
+
+impl std::str::FromStr for FieldStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "heatmap" => Ok(FieldStyle::Heatmap),
+            "contours" => Ok(FieldStyle::Contours),
+            "both" => Ok(FieldStyle::Both),
+            _ => Err(format!("Unknown field style: {}", s)),
+        }
+    }
+}
+
+/// How the gravitational field is drawn behind the bodies.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FieldConfig {
+    pub show: bool,
+    pub quantity: FieldQuantity,
+    pub style: FieldStyle,
+    /// Size of a cell of the sampled grid, in pixels. Smaller cells look smoother but take
+    /// longer.
+    pub resolution: f64,
+    pub map: ColorMap,
+    /// Number of contour lines, evenly spread over the range of values on a log scale.
+    pub contours: usize,
+}
+
+impl std::default::Default for FieldConfig {
+    fn default() -> Self {
+        Self {
+            show: false,
+            quantity: FieldQuantity::Potential,
+            style: FieldStyle::Heatmap,
+            resolution: 8.0,
+            map: ColorMap::Viridis,
+            contours: 12,
+        }
+    }
+}
+
+/// The field sampled at the centers of square cells covering the viewport.
+///
+/// Values are kept on a log scale, since they span orders of magnitude between the space around
+/// a sun and the space between planets: the log of the depth of the potential, or of the
+/// strength of the field. Where the field vanishes, the value is negative infinity.
+pub struct FieldGrid {
+    columns: usize,
+    rows: usize,
+    /// Size of a cell in pixels.
+    cell: f64,
+    /// Values row by row, from the top left.
+    values: Vec<f64>,
+}
+
+impl FieldGrid {
+    /// Sample the field of the bodies in `tree` across the viewport of `camera`, for a
+    /// gravitational constant of `g`. The tree approximates far away bodies, so sampling stays
+    /// affordable with many bodies.
+    pub fn sample(tree: &QuadNode, camera: &Camera, g: f64, cfg: &FieldConfig) -> Self {
+        let (width, height) = camera.viewport();
+        let cell = cfg.resolution.max(1.0);
+        let columns = (width / cell).ceil().max(1.0) as usize;
+        let rows = (height / cell).ceil().max(1.0) as usize;
+
+        let mut values = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let center = Point::new((column as f64 + 0.5) * cell, (row as f64 + 0.5) * cell);
+                let pos = camera.screen_to_world(center);
+                let value = match cfg.quantity {
+                    FieldQuantity::Potential => -tree.potential(pos, g),
+                    FieldQuantity::Strength => tree.acceleration(pos, Uuid::nil(), g).mag(),
+                };
+                values.push(value.log10());
+            }
+        }
+
+        Self {
+            columns,
+            rows,
+            cell,
+            values,
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Size of a cell in pixels.
+    pub fn cell(&self) -> f64 {
+        self.cell
+    }
+
+    /// The value sampled at the center of a cell.
+    pub fn value(&self, column: usize, row: usize) -> f64 {
+        self.values[row * self.columns + column]
+    }
+
+    /// The position of a cell's center on the screen.
+    pub fn center(&self, column: usize, row: usize) -> Point {
+        Point::new(
+            (column as f64 + 0.5) * self.cell,
+            (row as f64 + 0.5) * self.cell,
+        )
+    }
+
+    /// Smallest and largest finite value, if there are any.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.values
+            .iter()
+            .filter(|v| v.is_finite())
+            .fold(None, |range, &v| match range {
+                Some((min, max)) => Some((v.min(min), v.max(max))),
+                None => Some((v, v)),
+            })
+    }
+
+    /// Where the sampled field crosses `level`, as line segments on the screen. Found by
+    /// marching squares between the centers of neighbouring cells.
+    pub fn contour(&self, level: f64) -> Vec<[Point; 2]> {
+        let mut segments = vec![];
+        for row in 0..self.rows.saturating_sub(1) {
+            for column in 0..self.columns.saturating_sub(1) {
+                // Corners going round the square, starting top left.
+                let corners = [
+                    (column, row),
+                    (column + 1, row),
+                    (column + 1, row + 1),
+                    (column, row + 1),
+                ];
+                let values = corners.map(|(c, r)| self.value(c, r));
+                let points = corners.map(|(c, r)| self.center(c, r));
+                let above = values.map(|v| v > level);
+
+                // Where the level crosses each edge, if it does.
+                let crossing = |i: usize| {
+                    let j = (i + 1) % 4;
+                    if above[i] == above[j] {
+                        return None;
+                    }
+                    let t = if values[j].is_finite() && values[i].is_finite() {
+                        (level - values[i]) / (values[j] - values[i])
+                    } else {
+                        0.5
+                    };
+                    Some(points[i] + (points[j] - points[i]) * t)
+                };
+                let crossings: Vec<Point> = (0..4).filter_map(crossing).collect();
+
+                match crossings.as_slice() {
+                    [a, b] => segments.push([*a, *b]),
+                    [a, b, c, d] => {
+                        // A saddle: pair the crossings around the corners the middle agrees with.
+                        let middle = values.iter().sum::<f64>() / 4.0 > level;
+                        if middle == above[0] {
+                            segments.push([*a, *b]);
+                            segments.push([*c, *d]);
+                        } else {
+                            segments.push([*d, *a]);
+                            segments.push([*b, *c]);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn well() {
+        let mut universe = Universe::new((200.0, 100.0));
+        universe.add(Planet::new_sun(100.0, 50.0));
+        let cfg = FieldConfig {
+            show: true,
+            resolution: 10.0,
+            ..FieldConfig::default()
+        };
+        let grid = universe.sample_field(&cfg);
+        assert_eq!((grid.columns(), grid.rows()), (20, 10));
+
+        // Deepest next to the sun, shallower further out.
+        let (min, max) = grid.range().unwrap();
+        assert_eq!(grid.value(9, 4), max);
+        assert!(grid.value(0, 0) < grid.value(5, 4));
+        assert!(grid.value(0, 0) - min < 1e-9);
+
+        // Contours of a single well go round it.
+        let level = (grid.value(9, 4) + grid.value(0, 0)) / 2.0;
+        let segments = grid.contour(level);
+        assert!(!segments.is_empty());
+        let sun = Point::new(100.0, 50.0);
+        let radii: Vec<f64> = segments.iter().map(|s| (s[0] - sun).mag()).collect();
+        let (lo, hi) = radii
+            .iter()
+            .fold((f64::MAX, 0.0f64), |(lo, hi), &r| (lo.min(r), hi.max(r)));
+        assert!(hi - lo < grid.cell() * 1.5);
+    }
+
+    #[test]
+    fn lagrange() {
+        // Between two equal masses, the pulls cancel out in the middle.
+        let mut universe = Universe::new((200.0, 100.0));
+        universe.add(Planet::with_mass(
+            Point::new(50.0, 50.0),
+            1e6,
+            5.0,
+            Point::default(),
+        ));
+        universe.add(Planet::with_mass(
+            Point::new(150.0, 50.0),
+            1e6,
+            5.0,
+            Point::default(),
+        ));
+        let cfg = FieldConfig {
+            quantity: FieldQuantity::Strength,
+            resolution: 10.0,
+            ..FieldConfig::default()
+        };
+        let grid = universe.sample_field(&cfg);
+        let middle = grid.value(9, 4).min(grid.value(10, 4));
+        assert!(middle < grid.value(7, 4));
+        assert!(middle < grid.value(9, 2));
+    }
+}
@@ -29,6 +29,12 @@ impl Point {
         let dy = (other_p.y - self.y).powf(2.0);
         (dx + dy).sqrt()
     }
+
+    /// Return the vector rotated counterclockwise by `angle` radians.
+    pub fn rotate(&self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Point::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
 }
 
 impl std::ops::Add for Point {
@@ -177,6 +183,8 @@ impl std::fmt::Display for Point {
 mod test {
     use super::*;
 
+    // We want to exercise the operators on references, too.
+    #[allow(clippy::op_ref)]
     #[test]
     fn basics() {
         let p1 = Point::new(1.0, 1.0);
@@ -195,4 +203,12 @@ mod test {
         assert_eq!(p3norm, Point { x: 0.6, y: 0.8 });
         assert_eq!(p3norm.mag(), 1.0);
     }
+
+    #[test]
+    fn rotate() {
+        let p = Point::new(3.0, 4.0).rotate(std::f64::consts::FRAC_PI_2);
+        assert!(p.distance_to(Point::new(-4.0, 3.0)) < 1e-12);
+        let p = p.rotate(-std::f64::consts::FRAC_PI_2);
+        assert!(p.distance_to(Point::new(3.0, 4.0)) < 1e-12);
+    }
 }
@@ -1,6 +1,7 @@
 use super::*;
 
 /// A cardinal direction.
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Cardinal {
     NW,
     NE,
@@ -10,7 +11,7 @@ pub enum Cardinal {
 
 /// A struct representing a rectangular plane in a Cartesian coordinate system.
 /// X and y co-ordinates specify the *center* of the rectangle.
-#[derive(Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Rect {
     x: f64,
     y: f64,
@@ -28,6 +29,55 @@ impl Rect {
         }
     }
 
+    /// Create the smallest `Rect` covering two points.
+    pub fn around(a: Point, b: Point) -> Self {
+        Self::new(
+            (a.x + b.x) / 2.0,
+            (a.y + b.y) / 2.0,
+            (a.x - b.x).abs(),
+            (a.y - b.y).abs(),
+        )
+    }
+
+    /// Grow the rectangle by `margin` on every side.
+    pub fn expand(&self, margin: f64) -> Self {
+        Self::new(
+            self.x,
+            self.y,
+            self.width + 2.0 * margin,
+            self.height + 2.0 * margin,
+        )
+    }
+
+    /// The smallest `Rect` covering both rectangles.
+    pub fn union(&self, other: &Rect) -> Self {
+        let (nw, se) = (self.corner(Cardinal::NW), self.corner(Cardinal::SE));
+        let (other_nw, other_se) = (other.corner(Cardinal::NW), other.corner(Cardinal::SE));
+        Self::around(
+            Point::new(nw.x.min(other_nw.x), nw.y.max(other_nw.y)),
+            Point::new(se.x.max(other_se.x), se.y.min(other_se.y)),
+        )
+    }
+
+    /// Distance from a point to the closest point of the rectangle, 0 if it's covered.
+    pub fn distance_to(&self, p: Point) -> f64 {
+        let dx = ((p.x - self.x).abs() - self.half_width()).max(0.0);
+        let dy = ((p.y - self.y).abs() - self.half_height()).max(0.0);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Whether two rectangles overlap, including touching edges.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        (self.x - other.x).abs() <= self.half_width() + other.half_width()
+            && (self.y - other.y).abs() <= self.half_height() + other.half_height()
+    }
+
+    /// Whether the point lies inside of the rectangle or on its edges, unlike
+    /// [`contains`](#method.contains).
+    pub fn covers(&self, p: &Point) -> bool {
+        (p.x - self.x).abs() <= self.half_width() && (p.y - self.y).abs() <= self.half_height()
+    }
+
     pub fn contains(&self, p: &Point) -> bool {
         let nw = self.corner(Cardinal::NW);
         let se = self.corner(Cardinal::SE);
@@ -98,6 +148,7 @@ impl std::fmt::Display for Rect {
 mod test {
     use super::*;
 
+    #[allow(clippy::bool_assert_comparison)]
     #[test]
     fn basics() {
         let a = Rect::new(5.0, 5.0, 10.0, 10.0);
@@ -116,4 +167,21 @@ mod test {
         assert_eq!(a.split_rect(Cardinal::SE), se);
         assert_eq!(a.split_rect(Cardinal::SW), sw);
     }
+
+    #[test]
+    fn edges() {
+        let a = Rect::new(5.0, 5.0, 10.0, 10.0);
+        let nw = a.split_rect(Cardinal::NW);
+        let ne = a.split_rect(Cardinal::NE);
+        let se = a.split_rect(Cardinal::SE);
+
+        assert!(nw.intersects(&ne));
+        assert!(!nw.intersects(&se.expand(-0.1)));
+        assert!(a.covers(&Point::new(10.0, 0.0)));
+        assert!(!a.contains(&Point::new(10.0, 0.0)));
+        assert_eq!(
+            Rect::around(Point::new(10.0, 0.0), Point::new(0.0, 10.0)),
+            a
+        );
+    }
 }
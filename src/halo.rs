@@ -0,0 +1,74 @@
+use super::*;
+
+/// An analytic dark matter halo, pulling every planet and test particle towards its center
+/// without being simulated itself.
+///
+/// The halo has a logarithmic potential Φ(r) = ½ v₀² ln(1 + r²/r꜀²). Its rotation curve rises
+/// within the core radius r꜀ and flattens out at v₀ beyond, like those observed in disk
+/// galaxies. The potential keeps growing with r, so unlike that of a planet it can't be 0 far
+/// away; it's measured from the center instead, where it's 0.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Halo {
+    pub center: Point,
+    /// Speed of circular orbits far outside the core, v₀.
+    pub velocity: f64,
+    /// Radius within which the density levels off, r꜀. Needs to be positive.
+    pub core: f64,
+}
+
+impl Halo {
+    pub fn new(center: Point, velocity: f64, core: f64) -> Self {
+        Self {
+            center,
+            velocity,
+            core,
+        }
+    }
+
+    /// Potential of the halo at `pos`, per unit mass, relative to the center.
+    pub fn potential(&self, pos: Point) -> f64 {
+        let r = pos.distance_to(self.center);
+        0.5 * self.velocity.powi(2) * (r * r / (self.core * self.core)).ln_1p()
+    }
+
+    /// Acceleration the halo causes on a body at `pos`.
+    pub fn acceleration(&self, pos: Point) -> Point {
+        let d = pos - self.center;
+        let r2 = d.x * d.x + d.y * d.y;
+        d * (-self.velocity.powi(2) / (r2 + self.core * self.core))
+    }
+
+    /// Speed of a circular orbit at a distance of `r` from the center, in the halo alone.
+    pub fn circular_velocity(&self, r: f64) -> f64 {
+        self.velocity * r / (r * r + self.core * self.core).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotation_curve() {
+        let halo = Halo::new(Point::new(10.0, 10.0), 2.0, 1.0);
+
+        // The acceleration is the slope of the potential.
+        let pos = Point::new(13.0, 14.0);
+        let h = 1e-6;
+        let slope = Point::new(
+            (halo.potential(pos + Point::new(h, 0.0)) - halo.potential(pos - Point::new(h, 0.0)))
+                / (2.0 * h),
+            (halo.potential(pos + Point::new(0.0, h)) - halo.potential(pos - Point::new(0.0, h)))
+                / (2.0 * h),
+        );
+        assert!((halo.acceleration(pos) + slope).mag() < 1e-6);
+        assert_eq!(halo.potential(halo.center), 0.0);
+        assert!(halo.potential(pos) > 0.0);
+
+        // Circular orbits are held by the pull, rising within the core and flat far out.
+        let (r, v) = (5.0, halo.circular_velocity(5.0));
+        assert!((halo.acceleration(pos).mag() - v * v / r).abs() < 1e-12);
+        assert!(halo.circular_velocity(0.1) < 0.2);
+        assert!((halo.circular_velocity(1000.0) - 2.0).abs() < 1e-5);
+    }
+}
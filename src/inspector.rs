@@ -0,0 +1,107 @@
+use uuid::Uuid;
+
+use super::*;
+
+/// The live properties of a single [`Planet`](../planet/struct.Planet.html), as shown in the
+/// inspector panel.
+///
+/// All values are kept in the units of the [`Universe`](../universe/struct.Universe.html) and
+/// converted to SI when displayed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Inspection {
+    pub id: Uuid,
+    pub pos: Point,
+    pub velocity: Point,
+    pub mass: f64,
+    pub radius: f64,
+    pub density: f64,
+    /// Net gravitational force all other planets exert on this one.
+    pub net_force: Point,
+    pub pinned: bool,
+    /// The orbit around the sun and its gravitational parameter μ, if there's a sun to orbit.
+    pub orbit: Option<(Orbit, f64)>,
+    pub units: UnitSystem,
+}
+
+impl Inspection {
+    pub fn speed(&self) -> f64 {
+        self.velocity.mag()
+    }
+}
+
+impl std::fmt::Display for Inspection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let u = &self.units;
+        let pos = self.pos * u.length_to_si(1.0);
+        let velocity = self.velocity * u.velocity_to_si(1.0);
+        let force = self.net_force * u.force_to_si(1.0);
+
+        writeln!(f, "{:>13}", "PLANET")?;
+        writeln!(f, "{:>12}: {}", "Id", self.id)?;
+        writeln!(f, "{:>12}: x: {:.4e}, y: {:.4e} m", "Pos", pos.x, pos.y)?;
+        writeln!(
+            f,
+            "{:>12}: x: {:.4e}, y: {:.4e} m/s",
+            "Velocity", velocity.x, velocity.y
+        )?;
+        writeln!(f, "{:>12}: {:.4e} m/s", "Speed", velocity.mag())?;
+        writeln!(f, "{:>12}: {:.4e} kg", "Mass", u.mass_to_si(self.mass))?;
+        writeln!(f, "{:>12}: {:.4e} m", "Radius", u.length_to_si(self.radius))?;
+        writeln!(
+            f,
+            "{:>12}: {:.4e} kg/m³",
+            "Density",
+            u.density_to_si(self.density)
+        )?;
+        writeln!(
+            f,
+            "{:>12}: x: {:.4e}, y: {:.4e} N",
+            "Net force", force.x, force.y
+        )?;
+        writeln!(f, "{:>12}: {:.4e} N", "Magnitude", force.mag())?;
+        writeln!(f, "{:>12}: {}", "Pinned", self.pinned)?;
+
+        if let Some((orbit, mu)) = self.orbit {
+            writeln!(f, "{:>13}", "ORBIT")?;
+            writeln!(
+                f,
+                "{:>12}: {:.4e} m",
+                "Semi-major",
+                u.length_to_si(orbit.semi_major_axis)
+            )?;
+            writeln!(f, "{:>12}: {:.4}", "Eccentric.", orbit.eccentricity)?;
+            writeln!(
+                f,
+                "{:>12}: {:.1}°",
+                "Arg. peri.",
+                orbit.arg_periapsis.to_degrees()
+            )?;
+            writeln!(
+                f,
+                "{:>12}: {:.1}°",
+                "True anom.",
+                orbit.true_anomaly.to_degrees()
+            )?;
+            writeln!(
+                f,
+                "{:>12}: {:.4e} m",
+                "Periapsis",
+                u.length_to_si(orbit.periapsis())
+            )?;
+            writeln!(
+                f,
+                "{:>12}: {:.4e} m",
+                "Apoapsis",
+                u.length_to_si(orbit.apoapsis())
+            )?;
+            writeln!(
+                f,
+                "{:>12}: {:.4e} s",
+                "Period",
+                u.time_to_si(orbit.period(mu))
+            )?;
+            writeln!(f, "{:>12}: {}", "Retrograde", orbit.retrograde)?;
+        }
+        Ok(())
+    }
+}
@@ -10,7 +10,7 @@
 //!
 //! To be able to efficiently render a large amount of planets, we reduce computations by constructing a [`quad`](./quad/index.html)
 //! tree which will aggregate the gravitational forces of far away planets.
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -21,7 +21,7 @@ pub use planet::Planet;
 pub mod geo;
 pub use geo::{Cardinal, Point, Rect};
 pub mod quad;
-pub use quad::{Body, Newtonian, QuadConfig, QuadNode};
+pub use quad::{Body, Interaction, Newtonian, NodeView, QuadConfig, QuadNode};
 
 mod renderloop;
 pub use renderloop::RenderLoop;
@@ -32,9 +32,88 @@ pub use renderloop::RenderLoop;
 mod universe;
 pub use universe::Universe;
 
+pub mod units;
+pub use units::UnitSystem;
+
+mod camera;
+pub use camera::Camera;
+
+pub mod orbit;
+pub use orbit::Orbit;
+
+pub mod scenario;
+
+mod spawn;
+pub use spawn::{SpawnConfig, SpawnMode};
+
+mod trail;
+pub use trail::{Trail, TrailConfig};
+
+mod overlay;
+pub use overlay::OverlayConfig;
+
+pub mod field;
+pub use field::{FieldConfig, FieldGrid, FieldQuantity, FieldStyle};
+
+pub mod particles;
+pub use particles::{ParticleConfig, Tracers};
+
+pub mod render;
+pub use render::{
+    AnimationConfig, AnimationFormat, CanvasRenderer, Glyphs, Quantization, Raster, Recorder,
+    Renderer, Svg, Terminal,
+};
+
+mod color;
+pub use color::{Color, ColorBy, ColorMap, Palette, StyleConfig};
+
+mod inspector;
+pub use inspector::Inspection;
+
+mod event;
+pub use event::Event;
+
+mod halo;
+pub use halo::Halo;
+
+mod boundary;
+pub use boundary::Boundary;
+
+pub mod collision;
+pub use collision::{CollisionModel, Outcome};
+
+mod solver;
+pub use solver::{Integrator, Solver};
+
+pub mod snapshot;
+pub use snapshot::{PlanetState, Snapshot, TracerState};
+
+pub mod trajectory;
+pub use trajectory::{Trajectory, TrajectoryFrame};
+
+mod bindings;
+pub use bindings::JsUniverse;
+
+#[cfg(test)]
+mod testing;
+
 const NO_OF_PLANETS: usize = 100;
-const SCALE_F: f64 = 10_000.;
-const EATING_FORCE: f64 = 400.0;
+/// Number of ticks a launched planet takes to cover the distance it was pulled back.
+pub const LAUNCH_TICKS: f64 = 100.0;
+/// Pixels the mouse has to be dragged before a click becomes a launch.
+const DRAG_THRESHOLD: f64 = 5.0;
+/// Number of opacity steps a trail is drawn in.
+const TRAIL_BANDS: usize = 16;
+/// Largest size of an arrowhead, in pixels.
+const ARROW_HEAD: f64 = 6.0;
+/// Pixels between test particles seeded on a grid.
+const PARTICLE_SPACING: f64 = 20.0;
+/// Pixels the cursor may be off a planet and still pick it.
+const PICK_TOLERANCE: f64 = 5.0;
+/// Zoom per pixel scrolled with the mouse wheel, on an exponential scale.
+const WHEEL_ZOOM: f64 = 0.002;
+/// Radians to rotate the view per pixel scrolled with the mouse wheel.
+const WHEEL_ROTATION: f64 = 0.002;
 
 #[wasm_bindgen]
 extern "C" {
@@ -53,6 +132,186 @@ fn get_dimensions(canvas: &web_sys::HtmlCanvasElement) -> (f64, f64) {
     (bounding_rect.width(), bounding_rect.height())
 }
 
+/// Position of a mouse event relative to the canvas, in pixels.
+fn get_mouse_position(canvas: &web_sys::HtmlCanvasElement, event: &web_sys::MouseEvent) -> Point {
+    let bounding_rect = (canvas.as_ref() as &web_sys::Element).get_bounding_client_rect();
+    Point::new(
+        event.client_x() as f64 - bounding_rect.left(),
+        event.client_y() as f64 - bounding_rect.top(),
+    )
+}
+
+/// Read the value of an `<input>` or `<select>` element.
+fn get_value(document: &web_sys::Document, id: &str) -> Option<String> {
+    let element = document.get_element_by_id(id)?;
+    match element.dyn_into::<web_sys::HtmlInputElement>() {
+        Ok(input) => Some(input.value()),
+        Err(element) => element
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .ok()
+            .map(|select| select.value()),
+    }
+}
+
+/// Set the value of an `<input>` element.
+fn set_value(document: &web_sys::Document, id: &str, value: &str) {
+    if let Some(input) = document
+        .get_element_by_id(id)
+        .and_then(|e| e.dyn_into::<web_sys::HtmlInputElement>().ok())
+    {
+        input.set_value(value);
+    }
+}
+
+/// Let the browser download `contents` as a file called `filename`.
+fn download(
+    document: &web_sys::Document,
+    filename: &str,
+    contents: &str,
+    mime: &str,
+) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from(contents));
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &blob_options(mime))?;
+    download_blob(document, filename, &blob)
+}
+
+/// Let the browser download `bytes` as a file called `filename`.
+fn download_bytes(
+    document: &web_sys::Document,
+    filename: &str,
+    bytes: &[u8],
+    mime: &str,
+) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &blob_options(mime))?;
+    download_blob(document, filename, &blob)
+}
+
+fn blob_options(mime: &str) -> web_sys::BlobPropertyBag {
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    options
+}
+
+fn download_blob(
+    document: &web_sys::Document,
+    filename: &str,
+    blob: &web_sys::Blob,
+) -> Result<(), JsValue> {
+    let url = web_sys::Url::create_object_url_with_blob(blob)?;
+
+    let link: web_sys::HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    link.set_href(&url);
+    link.set_download(filename);
+    link.click();
+    web_sys::Url::revoke_object_url(&url)
+}
+
+/// Show the live properties of the selected planet in the inspector panel, or hide the panel if
+/// there's no planet selected.
+fn show_inspector(document: &web_sys::Document, universe: &Universe) {
+    let panel = match document.get_element_by_id("inspector") {
+        Some(panel) => panel,
+        None => return,
+    };
+
+    match universe.selected() {
+        Some(planet) => {
+            let inspection = universe.inspect(&planet.borrow());
+            let _ = panel.remove_attribute("hidden");
+            if let Some(info) = document.get_element_by_id("inspector-info") {
+                info.set_text_content(Some(&inspection.to_string()));
+            }
+            if let Some(pin) = document.get_element_by_id("inspector-pin") {
+                let label = if inspection.pinned { "unpin" } else { "pin" };
+                pin.set_text_content(Some(label));
+            }
+        }
+        None => {
+            let _ = panel.set_attribute("hidden", "");
+        }
+    }
+}
+
+/// Fill the inspector's inputs with the mass and velocity of a planet, ready to be edited.
+fn fill_inspector(document: &web_sys::Document, planet: &Planet) {
+    let velocity = Newtonian::velocity(planet);
+    set_value(document, "inspector-mass", &planet.mass().to_string());
+    set_value(document, "inspector-vx", &velocity.x.to_string());
+    set_value(document, "inspector-vy", &velocity.y.to_string());
+}
+
+/// Read the spawn settings from the controls on the page. Missing or invalid values are left at
+/// their defaults.
+fn get_spawn_config(document: &web_sys::Document, retrograde: bool) -> SpawnConfig {
+    let mut cfg = SpawnConfig {
+        retrograde,
+        ..SpawnConfig::default()
+    };
+
+    if let Some(mode) = get_value(document, "spawn-mode").and_then(|v| v.parse().ok()) {
+        cfg.mode = mode;
+    }
+    let number = |id| get_value(document, id).and_then(|v| v.parse::<f64>().ok());
+    if let Some(mass) = number("spawn-mass").filter(|m| *m > 0.0) {
+        cfg.mass = mass;
+    }
+    if let Some(density) = number("spawn-density").filter(|d| *d > 0.0) {
+        cfg.density = density;
+    }
+    if let Some(e) = number("spawn-eccentricity").filter(|e| (0.0..1.0).contains(e)) {
+        cfg.eccentricity = e;
+    }
+
+    cfg
+}
+
+/// Read the colour settings from the controls on the page.
+fn get_style_config(document: &web_sys::Document) -> StyleConfig {
+    let mut cfg = StyleConfig::default();
+    if let Some(color_by) = get_value(document, "color-by").and_then(|v| v.parse().ok()) {
+        cfg.color_by = color_by;
+    }
+    if let Some(map) = get_value(document, "color-map").and_then(|v| v.parse().ok()) {
+        cfg.map = map;
+    }
+    cfg
+}
+
+/// Read the collision model from the controls on the page.
+fn get_collision_model(document: &web_sys::Document) -> CollisionModel {
+    let mut model = get_value(document, "collisions")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_default();
+    let restitution = get_value(document, "restitution")
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|r| (0.0..=1.0).contains(r));
+    if let Some(r) = restitution {
+        match &mut model {
+            CollisionModel::Elastic { restitution } | CollisionModel::Inelastic { restitution } => {
+                *restitution = r
+            }
+            _ => {}
+        }
+    }
+    model
+}
+
+/// Read the trail settings from the controls on the page.
+fn get_trail_config(document: &web_sys::Document) -> TrailConfig {
+    let mut cfg = TrailConfig::default();
+    if let Some(length) = get_value(document, "trail-length").and_then(|v| v.parse().ok()) {
+        cfg.length = length;
+    }
+    if let Some(fade) = get_value(document, "trail-fade")
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|f| *f >= 0.0)
+    {
+        cfg.fade = fade;
+    }
+    cfg
+}
+
 #[allow(dead_code, dyn_drop)]
 #[wasm_bindgen]
 pub struct ModuleHandler {
@@ -60,6 +319,15 @@ pub struct ModuleHandler {
     closures: Vec<Box<dyn Drop>>,
 }
 
+#[wasm_bindgen]
+impl ModuleHandler {
+    /// Call `listener` with every event of the running universe, see
+    /// [`JsUniverse::subscribe`](./struct.JsUniverse.html#method.subscribe).
+    pub fn subscribe(&self, listener: js_sys::Function) {
+        self.render_loop.borrow_mut().subscribe(listener);
+    }
+}
+
 #[wasm_bindgen]
 pub fn main(
     canvas: web_sys::HtmlCanvasElement,
@@ -83,7 +351,7 @@ pub fn main(
     let render_loop = Rc::new(RefCell::new(RenderLoop::new(
         universe.clone(),
         window,
-        document,
+        document.clone(),
         context,
         play_pause_btn.clone(),
     )));
@@ -117,6 +385,7 @@ pub fn main(
                 }
                 let mut universe = Universe::new(dimensions);
                 universe.init_random();
+                universe.carry_settings(&render_loop.borrow().universe());
                 render_loop.borrow_mut().replace_universe(universe);
                 render_loop.borrow_mut().play()?;
                 Ok(())
@@ -127,25 +396,469 @@ pub fn main(
         closures.push(Box::new(closure));
     }
 
+    // Pressing the left mouse button on the canvas marks where a planet will be spawned.
+    // Releasing it right away selects the planet under the cursor, or if there's none, puts a new
+    // one on an orbit. Dragging pulls it back like a slingshot. Dragging with the middle or right
+    // button pans the view.
+    let drag_start: Rc<Cell<Option<Point>>> = Rc::new(Cell::new(None));
+    let pan_from: Rc<Cell<Option<Point>>> = Rc::new(Cell::new(None));
+    let mouse_pos: Rc<Cell<Point>> = Rc::new(Cell::new(Point::default()));
+
+    {
+        let closure: Closure<dyn Fn(_)> = {
+            let canvas = canvas.clone();
+            let drag_start = drag_start.clone();
+            let pan_from = pan_from.clone();
+            Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+                let pos = get_mouse_position(&canvas, &event);
+                match event.button() {
+                    0 => drag_start.set(Some(pos)),
+                    _ => pan_from.set(Some(pos)),
+                }
+            }))
+        };
+        (canvas.as_ref() as &web_sys::EventTarget)
+            .add_event_listener_with_callback("mousedown", closure.as_ref().unchecked_ref())?;
+        closures.push(Box::new(closure));
+    }
+
     {
         let closure: Closure<dyn Fn(_)> = {
             let universe = universe.clone();
             let canvas = canvas.clone();
+            let drag_start = drag_start.clone();
+            let pan_from = pan_from.clone();
+            let mouse_pos = mouse_pos.clone();
             Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
-                let bounding_rect =
-                    (canvas.as_ref() as &web_sys::Element).get_bounding_client_rect();
-                let x = event.client_x() as f64 - bounding_rect.left();
-                let y = event.client_y() as f64 - bounding_rect.top();
+                let pos = get_mouse_position(&canvas, &event);
+                mouse_pos.set(pos);
 
-                universe.borrow_mut().add_planet(x, y);
+                if let Some(from) = pan_from.get() {
+                    universe.borrow_mut().camera_mut().pan(pos - from);
+                    pan_from.set(Some(pos));
+                }
+
+                if let Some(start) = drag_start.get() {
+                    let end = pos;
+                    let mut universe = universe.borrow_mut();
+                    let preview = if start.distance_to(end) < DRAG_THRESHOLD {
+                        None
+                    } else {
+                        let camera = universe.camera();
+                        Some((camera.screen_to_world(start), camera.screen_to_world(end)))
+                    };
+                    universe.set_launch_preview(preview);
+                }
             }))
         };
         (canvas.as_ref() as &web_sys::EventTarget)
-            .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+            .add_event_listener_with_callback("mousemove", closure.as_ref().unchecked_ref())?;
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn(_)> = {
+            let universe = universe.clone();
+            let canvas = canvas.clone();
+            let document = document.clone();
+            let drag_start = drag_start.clone();
+            let pan_from = pan_from.clone();
+            Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+                pan_from.set(None);
+                let start = match drag_start.take() {
+                    Some(start) => start,
+                    None => return,
+                };
+                let end = get_mouse_position(&canvas, &event);
+
+                // Holding shift sends the new planet on a retrograde orbit.
+                let cfg = get_spawn_config(&document, event.shift_key());
+                let mut universe = universe.borrow_mut();
+                universe.set_launch_preview(None);
+                let pos = universe.camera().screen_to_world(start);
+                // Holding alt drops a test particle instead, on an orbit or launched.
+                if event.alt_key() {
+                    if start.distance_to(end) < DRAG_THRESHOLD {
+                        universe.spawn_particle(pos);
+                    } else {
+                        let velocity =
+                            universe.launch_velocity(pos, universe.camera().screen_to_world(end));
+                        universe.add_particle(pos, velocity);
+                    }
+                    return;
+                }
+                if start.distance_to(end) < DRAG_THRESHOLD {
+                    let tolerance = PICK_TOLERANCE / universe.camera().scale();
+                    match universe.planet_at(pos, tolerance) {
+                        Some(planet) => {
+                            universe.select(Some(planet.borrow().id()));
+                            fill_inspector(&document, &planet.borrow());
+                        }
+                        None => universe.spawn_planet(pos, &cfg),
+                    }
+                    show_inspector(&document, &universe);
+                } else {
+                    let velocity =
+                        universe.launch_velocity(pos, universe.camera().screen_to_world(end));
+                    universe.add_planet_with_velocity(pos, velocity, &cfg);
+                }
+            }))
+        };
+        (canvas.as_ref() as &web_sys::EventTarget)
+            .add_event_listener_with_callback("mouseup", closure.as_ref().unchecked_ref())?;
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let drag_start = drag_start.clone();
+            let pan_from = pan_from.clone();
+            Closure::wrap(Box::new(move || {
+                drag_start.set(None);
+                pan_from.set(None);
+                universe.borrow_mut().set_launch_preview(None);
+            }))
+        };
+        (canvas.as_ref() as &web_sys::EventTarget)
+            .add_event_listener_with_callback("mouseleave", closure.as_ref().unchecked_ref())?;
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn(_)> = Closure::wrap(Box::new(|event: web_sys::MouseEvent| {
+            // The right mouse button pans, so keep the context menu out of the way.
+            event.prevent_default();
+        }));
+        (canvas.as_ref() as &web_sys::EventTarget)
+            .add_event_listener_with_callback("contextmenu", closure.as_ref().unchecked_ref())?;
+        closures.push(Box::new(closure));
+    }
+
+    // The mouse wheel zooms in on the cursor, or rotates the view while alt is held.
+    {
+        let closure: Closure<dyn Fn(_)> = {
+            let universe = universe.clone();
+            let canvas = canvas.clone();
+            Closure::wrap(Box::new(move |event: web_sys::WheelEvent| {
+                event.prevent_default();
+                let mut universe = universe.borrow_mut();
+                if event.alt_key() {
+                    universe
+                        .camera_mut()
+                        .rotate(event.delta_y() * WHEEL_ROTATION);
+                } else {
+                    let anchor = get_mouse_position(&canvas, &event);
+                    let factor = (-event.delta_y() * WHEEL_ZOOM).exp();
+                    universe.camera_mut().zoom_at(anchor, factor);
+                }
+            }))
+        };
+        (canvas.as_ref() as &web_sys::EventTarget)
+            .add_event_listener_with_callback("wheel", closure.as_ref().unchecked_ref())?;
+        closures.push(Box::new(closure));
+    }
+
+    // Pressing "f" follows the planet under the cursor, or stops following if there's none. "q"
+    // toggles the quad tree overlay, "v" the velocity arrows, "a" the acceleration arrows, "p"
+    // the gravitational field, "t" seeds test particles across the universe and "T" clears them,
+    // "r" resets the view and escape clears the selection.
+    {
+        let closure: Closure<dyn Fn(_)> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            let mouse_pos = mouse_pos.clone();
+            Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+                // Leave keys typed into the controls alone.
+                if let Some(target) = event.target() {
+                    if target.is_instance_of::<web_sys::HtmlInputElement>()
+                        || target.is_instance_of::<web_sys::HtmlTextAreaElement>()
+                        || target.is_instance_of::<web_sys::HtmlSelectElement>()
+                    {
+                        return;
+                    }
+                }
+                let mut universe = universe.borrow_mut();
+                match event.key().as_str() {
+                    "f" => {
+                        let camera = universe.camera();
+                        let pos = camera.screen_to_world(mouse_pos.get());
+                        let tolerance = PICK_TOLERANCE / camera.scale();
+                        let id = universe.planet_at(pos, tolerance).map(|p| p.borrow().id());
+                        universe.camera_mut().set_follow(id);
+                        universe.update_camera();
+                    }
+                    "Escape" => {
+                        universe.select(None);
+                        show_inspector(&document, &universe);
+                    }
+                    "q" => {
+                        let show = !universe.show_quad_tree();
+                        universe.set_show_quad_tree(show);
+                    }
+                    "v" => {
+                        let mut overlays = universe.overlays();
+                        overlays.velocities = !overlays.velocities;
+                        universe.set_overlays(overlays);
+                    }
+                    "a" => {
+                        let mut overlays = universe.overlays();
+                        overlays.accelerations = !overlays.accelerations;
+                        universe.set_overlays(overlays);
+                    }
+                    "p" => {
+                        let mut field = universe.field();
+                        field.show = !field.show;
+                        universe.set_field(field);
+                    }
+                    "t" => {
+                        let (width, height) = universe.dimensions();
+                        let area = Rect::new(width / 2.0, height / 2.0, width, height);
+                        let spacing = PARTICLE_SPACING / universe.camera().scale();
+                        universe.seed_particles(&area, spacing, true);
+                    }
+                    "T" => universe.clear_particles(),
+                    "r" => universe.reset_camera(),
+                    _ => {}
+                }
+            }))
+        };
+        (document.as_ref() as &web_sys::EventTarget)
+            .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
+        closures.push(Box::new(closure));
+    }
+
+    // The inspector's buttons act on the selected planet.
+    {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                let mut universe = universe.borrow_mut();
+                if let Some(planet) = universe.selected() {
+                    let id = planet.borrow().id();
+                    universe.remove(id);
+                }
+                show_inspector(&document, &universe);
+            }))
+        };
+        if let Some(button) = document.get_element_by_id("inspector-delete") {
+            (button.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                let svg = universe.borrow().to_svg();
+                if let Err(e) = download(&document, "liniverse.svg", &svg, "image/svg+xml") {
+                    web_sys::console::error_1(&e);
+                }
+            }))
+        };
+        if let Some(button) = document.get_element_by_id("save-svg") {
+            (button.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn()> = {
+            let render_loop = render_loop.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                let button = document.get_element_by_id("record");
+                let label = |text: &str| {
+                    if let Some(button) = &button {
+                        (button.as_ref() as &web_sys::Node).set_text_content(Some(text));
+                    }
+                };
+                let mut render_loop = render_loop.borrow_mut();
+                let result = match render_loop.stop_recording() {
+                    Some(Ok(gif)) => {
+                        label("rec");
+                        download_bytes(&document, "liniverse.gif", &gif, "image/gif")
+                    }
+                    Some(Err(e)) => {
+                        label("rec");
+                        Err(JsValue::from(e.as_str()))
+                    }
+                    None => {
+                        let (width, height) = render_loop.universe().camera().viewport();
+                        let mut cfg = AnimationConfig::new(
+                            AnimationFormat::Gif,
+                            width as usize,
+                            height as usize,
+                        );
+                        // The page draws at up to 60 frames per second.
+                        cfg.every = 2;
+                        render_loop.start_recording(cfg).map(|_| label("stop"))
+                    }
+                };
+                if let Err(e) = result {
+                    web_sys::console::error_1(&e);
+                }
+            }))
+        };
+        if let Some(button) = document.get_element_by_id("record") {
+            (button.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                let universe = universe.borrow();
+                if let Some(planet) = universe.selected() {
+                    let planet = planet.borrow();
+                    planet.pin(!planet.pinned());
+                }
+                show_inspector(&document, &universe);
+            }))
+        };
+        if let Some(button) = document.get_element_by_id("inspector-pin") {
+            (button.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                let universe = universe.borrow();
+                let planet = match universe.selected() {
+                    Some(planet) => planet,
+                    None => return,
+                };
+                let planet = planet.borrow();
+                let number = |id| get_value(&document, id).and_then(|v| v.parse::<f64>().ok());
+                if let Some(mass) = number("inspector-mass").filter(|m| *m > 0.0) {
+                    planet.set_mass(mass);
+                }
+                if let (Some(x), Some(y)) = (number("inspector-vx"), number("inspector-vy")) {
+                    planet.set_velocity(Point::new(x, y));
+                }
+                fill_inspector(&document, &planet);
+                show_inspector(&document, &universe);
+            }))
+        };
+        if let Some(button) = document.get_element_by_id("inspector-apply") {
+            (button.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    for id in &["trail-length", "trail-fade"] {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                let cfg = get_trail_config(&document);
+                universe.borrow_mut().set_trail_config(cfg);
+            }))
+        };
+        if let Some(input) = document.get_element_by_id(id) {
+            (input.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("input", closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                if let Some(theta) = get_value(&document, "theta")
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .filter(|t| *t >= 0.0)
+                {
+                    universe.borrow_mut().set_theta(theta);
+                }
+            }))
+        };
+        if let Some(input) = document.get_element_by_id("theta") {
+            (input.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("input", closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                if let Some(boundary) =
+                    get_value(&document, "boundary").and_then(|v| v.parse().ok())
+                {
+                    universe.borrow_mut().set_boundary(boundary);
+                }
+            }))
+        };
+        if let Some(select) = document.get_element_by_id("boundary") {
+            (select.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("change", closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    for (id, event) in &[("collisions", "change"), ("restitution", "input")] {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                let model = get_collision_model(&document);
+                universe.borrow_mut().set_collisions(model);
+            }))
+        };
+        if let Some(input) = document.get_element_by_id(id) {
+            (input.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())?;
+        }
+        closures.push(Box::new(closure));
+    }
+
+    for id in &["color-by", "color-map"] {
+        let closure: Closure<dyn Fn()> = {
+            let universe = universe.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                let cfg = get_style_config(&document);
+                universe.borrow_mut().set_style(cfg);
+            }))
+        };
+        if let Some(select) = document.get_element_by_id(id) {
+            (select.as_ref() as &web_sys::EventTarget)
+                .add_event_listener_with_callback("change", closure.as_ref().unchecked_ref())?;
+        }
         closures.push(Box::new(closure));
     }
 
     universe.borrow_mut().init_random();
+    universe
+        .borrow_mut()
+        .set_trail_config(get_trail_config(&document));
+    universe.borrow_mut().set_style(get_style_config(&document));
+    if let Some(boundary) = get_value(&document, "boundary").and_then(|v| v.parse().ok()) {
+        universe.borrow_mut().set_boundary(boundary);
+    }
+    universe
+        .borrow_mut()
+        .set_collisions(get_collision_model(&document));
     render_loop.borrow_mut().play()?;
 
     Ok(ModuleHandler {
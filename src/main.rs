@@ -0,0 +1,788 @@
+//! Headless runs of a [`Universe`](../liniverse/struct.Universe.html) from the command line.
+//!
+//! ```text
+//! liniverse --steps 10000 --every 500 --integrator leapfrog --snapshots out solar-system
+//! liniverse --animation replay.gif --palette web-safe trajectory.csv
+//! liniverse --live --steps 100000 --trail-length 200 solar-system
+//! ```
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use liniverse::{
+    scenario, AnimationConfig, AnimationFormat, Boundary, CollisionModel, ColorBy, Event,
+    FieldConfig, Glyphs, Integrator, OverlayConfig, ParticleConfig, Quantization, Raster, Recorder,
+    Rect, Snapshot, Solver, Terminal, Trajectory, TrajectoryFrame, Universe,
+};
+
+const USAGE: &str = "\
+Usage: liniverse [options] <scenario, snapshot or trajectory file>
+
+Runs a universe without drawing it and prints its energy and body count along the way.
+Scenarios are random, solar-system, solar-system-moons, galaxy and galaxy-tracers. Files
+ending in .csv are read as trajectories written by --trajectory and replayed frame by frame
+to draw them, anything else is read as a snapshot file.
+
+Options:
+  --steps <n>           Ticks to run, defaults to 1000
+  --every <n>           Ticks between diagnostics and output, defaults to 100
+  --dt <dt>             Time passing on each tick, in the time units of the universe
+  --integrator <name>   euler or leapfrog, defaults to the scenario's own
+  --solver <name>       direct or barnes-hut, defaults to the scenario's own
+  --theta <theta>       Barnes-Hut threshold, defaults to 0.5
+  --softening <length>  Softening length of gravity, defaults to the scenario's own
+  --boundary <name>     wrap or open
+  --collisions <name>   merge, elastic, inelastic or fragment
+  --size <w>x<h>        Canvas the scenarios are laid out for, defaults to 800x600
+  --snapshots <dir>     Write a snapshot to <dir>/snapshot-<step>.txt at every interval
+  --trajectory <file>   Write the state of every body at every interval as CSV
+  --frames <dir>        Draw the universe to <dir>/frame-<n>.png, numbered from 0
+  --frame-every <n>     Ticks between frames, animation frames and terminal updates,
+                        defaults to the interval of --every, or 1 with --live and for
+                        replayed trajectories
+  --trail-length <n>    Past positions drawn behind each body in frames and SVG
+  --color-by <name>     Colour bodies in frames and SVG by body, mass, density, speed,
+                        kinetic-energy, age or merges
+  --quad-tree           Draw the quad tree in frames and SVG
+  --velocities          Draw velocity arrows in frames and SVG
+  --velocity-scale <n>  Ticks of travel velocity arrows reach ahead, defaults to 100
+  --accelerations       Draw arrows for the net gravitational pull in frames and SVG
+  --acceleration-scale <n>
+                        Ticks of pull acceleration arrows are scaled to, defaults to 100
+  --field <name>        Draw the potential or the strength of the gravitational field
+                        behind the bodies in frames and SVG
+  --field-style <name>  heatmap, contours or both, defaults to heatmap
+  --field-resolution <n>
+                        Pixels per cell of the sampled field, defaults to 8
+  --field-map <name>    Colour map of the field, viridis, heat, coolwarm or grayscale
+  --contours <n>        Contour lines drawn, defaults to 12
+  --particles <n>       Release massless test particles on orbits, on a grid <n> pixels
+                        apart across the universe
+  --absorb              Remove test particles touching a body
+  --streamlines <n>     Past positions drawn behind each test particle
+  --svg <file>          Draw the universe at the end of the run as SVG
+  --animation <file>    Record the run as animated GIF, or APNG for .png and .apng files
+  --animation-size <w>x<h>
+                        Size of the animation, defaults to the size of --size
+  --fps <fps>           Frames per second the animation plays at, defaults to 30
+  --palette <name>      Colours of GIF frames, adaptive or web-safe
+  --colors <n>          Colours of adaptive GIF palettes, at most 256
+  --live                Draw the universe in the terminal as it runs, with a status line
+                        instead of the diagnostics table, at most --fps times a second
+  --terminal <c>x<r>    Columns and rows to draw in, defaults to the terminal's size
+  --glyphs <name>       braille or blocks, defaults to braille
+  --no-color            Draw in the terminal without colours
+  -h, --help            Show this message";
+
+struct Options {
+    source: String,
+    steps: u64,
+    every: u64,
+    dt: Option<f64>,
+    integrator: Option<Integrator>,
+    solver: Option<Solver>,
+    theta: Option<f64>,
+    softening: Option<f64>,
+    boundary: Option<Boundary>,
+    collisions: Option<CollisionModel>,
+    size: (f64, f64),
+    snapshots: Option<PathBuf>,
+    trajectory: Option<PathBuf>,
+    frames: Option<PathBuf>,
+    frame_every: Option<u64>,
+    trail_length: Option<usize>,
+    color_by: Option<ColorBy>,
+    quad_tree: bool,
+    overlays: OverlayConfig,
+    field: FieldConfig,
+    particles: Option<f64>,
+    particle_config: ParticleConfig,
+    svg: Option<PathBuf>,
+    animation: Option<PathBuf>,
+    animation_size: Option<(f64, f64)>,
+    fps: f64,
+    palette: Quantization,
+    live: bool,
+    terminal: Option<(usize, usize)>,
+    glyphs: Glyphs,
+    color: bool,
+}
+
+impl Options {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            source: String::new(),
+            steps: 1000,
+            every: 100,
+            dt: None,
+            integrator: None,
+            solver: None,
+            theta: None,
+            softening: None,
+            boundary: None,
+            collisions: None,
+            size: (800.0, 600.0),
+            snapshots: None,
+            trajectory: None,
+            frames: None,
+            frame_every: None,
+            trail_length: None,
+            color_by: None,
+            quad_tree: false,
+            overlays: OverlayConfig::default(),
+            field: FieldConfig::default(),
+            particles: None,
+            particle_config: ParticleConfig::default(),
+            svg: None,
+            animation: None,
+            animation_size: None,
+            fps: 30.0,
+            palette: Quantization::default(),
+            live: false,
+            terminal: None,
+            glyphs: Glyphs::Braille,
+            color: true,
+        };
+
+        let mut source = None;
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                if source.replace(arg).is_some() {
+                    return Err("Only one scenario or snapshot can be run".to_string());
+                }
+                continue;
+            }
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--steps" => options.steps = parse(&arg, &value()?)?,
+                "--every" => options.every = parse(&arg, &value()?)?,
+                "--dt" => options.dt = Some(parse(&arg, &value()?)?),
+                "--integrator" => options.integrator = Some(value()?.parse()?),
+                "--solver" => options.solver = Some(value()?.parse()?),
+                "--theta" => options.theta = Some(parse(&arg, &value()?)?),
+                "--softening" => options.softening = Some(parse(&arg, &value()?)?),
+                "--boundary" => options.boundary = Some(value()?.parse()?),
+                "--collisions" => options.collisions = Some(value()?.parse()?),
+                "--size" => options.size = size(&arg, &value()?)?,
+                "--snapshots" => options.snapshots = Some(value()?.into()),
+                "--trajectory" => options.trajectory = Some(value()?.into()),
+                "--frames" => options.frames = Some(value()?.into()),
+                "--frame-every" => options.frame_every = Some(parse(&arg, &value()?)?),
+                "--trail-length" => options.trail_length = Some(parse(&arg, &value()?)?),
+                "--color-by" => options.color_by = Some(value()?.parse()?),
+                "--quad-tree" => options.quad_tree = true,
+                "--velocities" => options.overlays.velocities = true,
+                "--velocity-scale" => options.overlays.velocity_scale = parse(&arg, &value()?)?,
+                "--accelerations" => options.overlays.accelerations = true,
+                "--acceleration-scale" => {
+                    options.overlays.acceleration_scale = parse(&arg, &value()?)?
+                }
+                "--field" => {
+                    options.field.quantity = value()?.parse()?;
+                    options.field.show = true;
+                }
+                "--field-style" => options.field.style = value()?.parse()?,
+                "--field-resolution" => options.field.resolution = parse(&arg, &value()?)?,
+                "--field-map" => options.field.map = value()?.parse()?,
+                "--contours" => options.field.contours = parse(&arg, &value()?)?,
+                "--particles" => options.particles = Some(parse(&arg, &value()?)?),
+                "--absorb" => options.particle_config.absorb = true,
+                "--streamlines" => options.particle_config.streamlines = parse(&arg, &value()?)?,
+                "--svg" => options.svg = Some(value()?.into()),
+                "--animation" => options.animation = Some(value()?.into()),
+                "--animation-size" => options.animation_size = Some(size(&arg, &value()?)?),
+                "--fps" => options.fps = parse(&arg, &value()?)?,
+                "--palette" => {
+                    options.palette = match (value()?.parse()?, options.palette) {
+                        // Keep colours given before the palette.
+                        (Quantization::Adaptive { .. }, Quantization::Adaptive { colors }) => {
+                            Quantization::Adaptive { colors }
+                        }
+                        (palette, _) => palette,
+                    }
+                }
+                "--colors" => {
+                    options.palette = Quantization::Adaptive {
+                        colors: parse(&arg, &value()?)?,
+                    }
+                }
+                "--live" => options.live = true,
+                "--terminal" => {
+                    let (columns, rows) = size(&arg, &value()?)?;
+                    options.terminal = Some((columns as usize, rows as usize));
+                }
+                "--glyphs" => options.glyphs = value()?.parse()?,
+                "--no-color" => options.color = false,
+                "-h" | "--help" => return Err(String::new()),
+                _ => return Err(format!("Unknown option: {}", arg)),
+            }
+        }
+
+        options.source = source.ok_or("Missing scenario or snapshot")?;
+        if options.every == 0 || options.frame_every == Some(0) {
+            return Err("Intervals need to be at least 1".to_string());
+        }
+        Ok(options)
+    }
+}
+
+fn parse<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", option, value))
+}
+
+/// Parse a size like `800x600`.
+fn size(option: &str, value: &str) -> Result<(f64, f64), String> {
+    let mut parts = value.splitn(2, 'x');
+    let width = parse(option, parts.next().unwrap_or_default())?;
+    let height = parse(option, parts.next().unwrap_or_default())?;
+    Ok((width, height))
+}
+
+/// Load the scenario or snapshot to run, configured by `options`.
+fn load(options: &Options) -> Result<Universe, String> {
+    let mut universe = match scenario::by_name(&options.source, options.size) {
+        Some(universe) => universe,
+        None => {
+            let text = fs::read_to_string(&options.source)
+                .map_err(|e| format!("Can't read {}: {}", options.source, e))?;
+            let snapshot: Snapshot = text
+                .parse()
+                .map_err(|e| format!("Can't load {}: {}", options.source, e))?;
+            let mut universe = Universe::new(options.size);
+            universe.restore(&snapshot);
+            universe
+        }
+    };
+
+    if let Some(integrator) = options.integrator {
+        universe.set_integrator(integrator);
+    }
+    if let Some(solver) = options.solver {
+        universe.set_solver(solver);
+    }
+    if let Some(dt) = options.dt {
+        universe.set_dt(dt);
+    }
+    if let Some(theta) = options.theta {
+        universe.set_theta(theta);
+    }
+    if let Some(softening) = options.softening {
+        universe.set_softening(softening);
+    }
+    if let Some(boundary) = options.boundary {
+        universe.set_boundary(boundary);
+    }
+    if let Some(collisions) = options.collisions {
+        universe.set_collisions(collisions);
+    }
+    universe.set_particle_config(options.particle_config);
+    if let Some(spacing) = options.particles {
+        let (width, height) = universe.dimensions();
+        let area = Rect::new(width / 2.0, height / 2.0, width, height);
+        let spacing = spacing / universe.camera().scale();
+        universe.seed_particles(&area, spacing, true);
+    }
+    style(&mut universe, options);
+    Ok(universe)
+}
+
+/// Set up how `universe` is drawn in frames, animations and SVG.
+fn style(universe: &mut Universe, options: &Options) {
+    if let Some(length) = options.trail_length {
+        let mut trails = universe.trail_config();
+        trails.length = length;
+        universe.set_trail_config(trails);
+    }
+    if let Some(color_by) = options.color_by {
+        let mut style = universe.style();
+        style.color_by = color_by;
+        universe.set_style(style);
+    }
+    universe.set_show_quad_tree(options.quad_tree);
+    universe.set_overlays(options.overlays);
+    universe.set_field(options.field);
+}
+
+/// Columns and rows of the terminal, as told by `stty`, falling back to `COLUMNS` and `LINES`
+/// and then 80 by 24.
+fn terminal_size() -> (usize, usize) {
+    let stty = File::open("/dev/tty").ok().and_then(|tty| {
+        let output = Command::new("stty")
+            .arg("size")
+            .stdin(Stdio::from(tty))
+            .output()
+            .ok()?;
+        let size = String::from_utf8(output.stdout).ok()?;
+        let mut parts = size.split_whitespace().map(|n| n.parse().ok());
+        let rows = parts.next()??;
+        Some((parts.next()??, rows))
+    });
+    stty.unwrap_or_else(|| {
+        let var = |name: &str| std::env::var(name).ok().and_then(|n| n.parse().ok());
+        (var("COLUMNS").unwrap_or(80), var("LINES").unwrap_or(24))
+    })
+}
+
+/// Draws the universe into the terminal over and over, with a status line below.
+struct Live {
+    terminal: Terminal,
+    interval: Duration,
+    last: Option<Instant>,
+    /// Whether updates were skipped since the screen was last drawn.
+    behind: bool,
+    /// Total energy at the first update, to show the drift from.
+    initial: Option<f64>,
+}
+
+impl Live {
+    fn new(options: &Options) -> Self {
+        let (columns, rows) = options.terminal.unwrap_or_else(terminal_size);
+        // Leave a row for the status line.
+        let terminal = Terminal::new(columns, rows.saturating_sub(1).max(1), options.size)
+            .with_glyphs(options.glyphs)
+            .with_color(options.color);
+        Self {
+            terminal,
+            interval: Duration::from_secs_f64(1.0 / options.fps.max(1e-3)),
+            last: None,
+            behind: false,
+            initial: None,
+        }
+    }
+
+    /// Draw the universe, unless the last drawing is less than a frame ago. The simulation
+    /// keeps running at full speed in between.
+    fn update(&mut self, step: u64, universe: &Universe) -> Result<(), String> {
+        match self.last {
+            Some(last) if last.elapsed() < self.interval => {
+                self.behind = true;
+                Ok(())
+            }
+            _ => self.show(step, universe),
+        }
+    }
+
+    fn show(&mut self, step: u64, universe: &Universe) -> Result<(), String> {
+        // Clear the screen on the first update, then draw over the previous one.
+        let home = match self.last {
+            Some(_) => "\x1b[H",
+            None => "\x1b[2J\x1b[H",
+        };
+        self.last = Some(Instant::now());
+        self.behind = false;
+
+        universe.render(&mut self.terminal);
+        let (kinetic, potential) = universe.energy();
+        let total = kinetic + potential;
+        let initial = *self.initial.get_or_insert(total);
+        let drift = if initial == 0.0 {
+            0.0
+        } else {
+            (total - initial) / initial.abs()
+        };
+
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        write!(
+            out,
+            "{}{}step {}  time {:.4}  bodies {}  particles {}  energy {:.6e}  drift {:.3e}\x1b[K",
+            home,
+            self.terminal,
+            step,
+            universe.time(),
+            universe.planets().len(),
+            universe.particles().len(),
+            total,
+            drift
+        )
+        .map_err(io)?;
+        out.flush().map_err(io)
+    }
+}
+
+/// Everything drawn along a run: PNG frames, the animation and the terminal.
+struct Drawing {
+    frames: Option<(PathBuf, Raster)>,
+    frame: usize,
+    recorder: Option<Recorder>,
+    live: Option<Live>,
+    every: u64,
+}
+
+impl Drawing {
+    /// Prepare to draw every `every`-th of the steps passed to [`draw`](#method.draw).
+    fn new(options: &Options, every: u64) -> Result<Self, String> {
+        let frames = match &options.frames {
+            Some(dir) => {
+                fs::create_dir_all(dir).map_err(io)?;
+                let (width, height) = options.size;
+                Some((dir.clone(), Raster::new(width as usize, height as usize)))
+            }
+            None => None,
+        };
+        let recorder = match &options.animation {
+            Some(path) => {
+                let format = match path.extension().and_then(|e| e.to_str()) {
+                    Some(extension) => extension.parse().unwrap_or(AnimationFormat::Gif),
+                    None => AnimationFormat::Gif,
+                };
+                let (width, height) = options.animation_size.unwrap_or(options.size);
+                let mut cfg = AnimationConfig::new(format, width as usize, height as usize);
+                cfg.fps = options.fps;
+                cfg.palette = options.palette;
+                Some(Recorder::new(cfg)?)
+            }
+            None => None,
+        };
+        let live = if options.live {
+            Some(Live::new(options))
+        } else {
+            None
+        };
+        Ok(Self {
+            frames,
+            frame: 0,
+            recorder,
+            live,
+            every,
+        })
+    }
+
+    fn draw(&mut self, step: u64, universe: &Universe) -> Result<(), String> {
+        if step % self.every != 0 {
+            return Ok(());
+        }
+        if let Some((dir, raster)) = &mut self.frames {
+            universe.render(raster);
+            let path = dir.join(format!("frame-{:05}.png", self.frame));
+            let file = BufWriter::new(File::create(path).map_err(io)?);
+            raster.write_png(file).map_err(|e| e.to_string())?;
+            self.frame += 1;
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.capture(universe)?;
+        }
+        if let Some(live) = &mut self.live {
+            live.update(step, universe)?;
+        }
+        Ok(())
+    }
+
+    /// Write the animation and the final state as SVG, if asked for.
+    fn finish(self, universe: &Universe, options: &Options) -> Result<(), String> {
+        if let Some(mut live) = self.live {
+            // Make sure the final state is on screen.
+            if live.behind {
+                live.show(options.steps, universe)?;
+            }
+            println!();
+        }
+        if let (Some(recorder), Some(path)) = (self.recorder, &options.animation) {
+            fs::write(path, recorder.finish()?).map_err(io)?;
+        }
+        if let Some(path) = &options.svg {
+            fs::write(path, universe.to_svg()).map_err(io)?;
+        }
+        Ok(())
+    }
+}
+
+fn io(e: std::io::Error) -> String {
+    e.to_string()
+}
+
+/// Draw the frames of a trajectory written by an earlier run.
+fn replay(options: &Options) -> Result<(), String> {
+    let text = fs::read_to_string(&options.source)
+        .map_err(|e| format!("Can't read {}: {}", options.source, e))?;
+    let trajectory: Trajectory = text
+        .parse()
+        .map_err(|e| format!("Can't load {}: {}", options.source, e))?;
+
+    let mut universe = trajectory.universe(options.size);
+    style(&mut universe, options);
+    let mut drawing = Drawing::new(options, options.frame_every.unwrap_or(1))?;
+    for (i, frame) in trajectory.frames.iter().enumerate() {
+        universe.replay(frame);
+        drawing.draw(i as u64, &universe)?;
+    }
+    println!("Replayed {} frames", trajectory.frames.len());
+    drawing.finish(&universe, options)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    if options.source.ends_with(".csv") {
+        return replay(options);
+    }
+    let mut universe = load(options)?;
+
+    if let Some(dir) = &options.snapshots {
+        fs::create_dir_all(dir).map_err(io)?;
+    }
+    let mut trajectory = match &options.trajectory {
+        Some(path) => {
+            let mut file = BufWriter::new(File::create(path).map_err(io)?);
+            writeln!(file, "{}", Trajectory::HEADER).map_err(io)?;
+            Some(file)
+        }
+        None => None,
+    };
+    let every = match options.frame_every {
+        Some(every) => every,
+        None if options.live => 1,
+        None => options.every,
+    };
+    let mut drawing = Drawing::new(options, every)?;
+
+    let (kinetic, potential) = universe.energy();
+    let initial = kinetic + potential;
+    let (mut merges, mut escapes) = (0, 0);
+
+    if !options.live {
+        println!(
+            "{:>8} {:>12} {:>7} {:>7} {:>7} {:>13} {:>13} {:>13} {:>11}",
+            "step", "time", "bodies", "merges", "escapes", "kinetic", "potential", "total", "drift"
+        );
+    }
+    for step in 0..=options.steps {
+        if step > 0 {
+            universe.tick();
+            for event in universe.drain_events() {
+                match event {
+                    Event::Merge { .. } => merges += 1,
+                    Event::Escape { .. } => escapes += 1,
+                    _ => {}
+                }
+            }
+        }
+        drawing.draw(step, &universe)?;
+        if step % options.every != 0 && step != options.steps {
+            continue;
+        }
+
+        if !options.live {
+            print_diagnostics(step, &universe, initial, merges, escapes);
+        }
+
+        if let Some(dir) = &options.snapshots {
+            let path = dir.join(format!("snapshot-{:08}.txt", step));
+            fs::write(path, universe.snapshot().to_string()).map_err(io)?;
+        }
+        if let Some(file) = &mut trajectory {
+            let frame = TrajectoryFrame::capture(step, &universe);
+            write!(file, "{}", frame).map_err(io)?;
+        }
+    }
+
+    if let Some(file) = &mut trajectory {
+        file.flush().map_err(io)?;
+    }
+    drawing.finish(&universe, options)
+}
+
+/// Print a row of the diagnostics table.
+fn print_diagnostics(step: u64, universe: &Universe, initial: f64, merges: usize, escapes: usize) {
+    let (kinetic, potential) = universe.energy();
+    let total = kinetic + potential;
+    let drift = if initial == 0.0 {
+        0.0
+    } else {
+        (total - initial) / initial.abs()
+    };
+    println!(
+        "{:>8} {:>12.4} {:>7} {:>7} {:>7} {:>13.6e} {:>13.6e} {:>13.6e} {:>11.3e}",
+        step,
+        universe.time(),
+        universe.planets().len(),
+        merges,
+        escapes,
+        kinetic,
+        potential,
+        total,
+        drift
+    );
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) if e.is_empty() => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(&options) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn options(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(size("--size", "800x600"), Ok((800.0, 600.0)));
+        assert_eq!(size("--size", "1.5x2"), Ok((1.5, 2.0)));
+        assert!(size("--size", "800").is_err());
+        assert!(size("--size", "800x").is_err());
+        assert!(size("--size", "x600").is_err());
+        assert!(size("--size", "wide").is_err());
+    }
+
+    #[test]
+    fn parsing() {
+        let parsed = options(&["solar-system"]).unwrap();
+        assert_eq!(parsed.source, "solar-system");
+        assert_eq!(parsed.steps, 1000);
+        assert_eq!(parsed.every, 100);
+        assert_eq!(parsed.size, (800.0, 600.0));
+        assert!(parsed.dt.is_none());
+        assert!(!parsed.live);
+
+        let parsed = options(&[
+            "--steps",
+            "50",
+            "--every",
+            "5",
+            "--dt",
+            "0.25",
+            "--integrator",
+            "leapfrog",
+            "--theta",
+            "0.7",
+            "--size",
+            "320x240",
+            "--terminal",
+            "100x40",
+            "--colors",
+            "16",
+            "--quad-tree",
+            "--field",
+            "potential",
+            "galaxy",
+        ])
+        .unwrap();
+        assert_eq!(parsed.source, "galaxy");
+        assert_eq!(parsed.steps, 50);
+        assert_eq!(parsed.every, 5);
+        assert_eq!(parsed.dt, Some(0.25));
+        assert_eq!(parsed.integrator, Some(Integrator::Leapfrog));
+        assert_eq!(parsed.theta, Some(0.7));
+        assert_eq!(parsed.size, (320.0, 240.0));
+        assert_eq!(parsed.terminal, Some((100, 40)));
+        assert!(parsed.quad_tree);
+        assert!(parsed.field.show);
+        match parsed.palette {
+            Quantization::Adaptive { colors } => assert_eq!(colors, 16),
+            _ => panic!("expected an adaptive palette"),
+        }
+    }
+
+    #[test]
+    fn bad_options() {
+        let error = |args: &[&str]| match options(args) {
+            Ok(_) => panic!("{:?} should not parse", args),
+            Err(e) => e,
+        };
+        assert_eq!(error(&["--wobble", "random"]), "Unknown option: --wobble");
+        assert_eq!(
+            error(&["--steps", "many", "random"]),
+            "Invalid value for --steps: many"
+        );
+        assert_eq!(
+            error(&["--dt", "0.1.2", "random"]),
+            "Invalid value for --dt: 0.1.2"
+        );
+        assert_eq!(
+            error(&["--size", "800by600", "random"]),
+            "Invalid value for --size: 800by600"
+        );
+        assert_eq!(error(&["random", "--steps"]), "Missing value for --steps");
+        assert_eq!(error(&["--steps", "10"]), "Missing scenario or snapshot");
+        assert_eq!(
+            error(&["random", "galaxy"]),
+            "Only one scenario or snapshot can be run"
+        );
+        assert_eq!(
+            error(&["--every", "0", "random"]),
+            "Intervals need to be at least 1"
+        );
+        assert!(!error(&["--integrator", "rk9", "random"]).is_empty());
+        assert!(error(&["--help"]).is_empty());
+    }
+
+    #[test]
+    fn headless_run() {
+        let dir = std::env::temp_dir().join(format!("liniverse-test-{}", std::process::id()));
+        let snapshots = dir.join("snapshots");
+        let trajectory = dir.join("trajectory.csv");
+        let parsed = options(&[
+            "--steps",
+            "20",
+            "--every",
+            "10",
+            "--snapshots",
+            snapshots.to_str().unwrap(),
+            "--trajectory",
+            trajectory.to_str().unwrap(),
+            "solar-system",
+        ])
+        .unwrap();
+        run(&parsed).unwrap();
+
+        // Snapshots at the first step, every tenth and the last.
+        let mut written: Vec<_> = fs::read_dir(&snapshots)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        written.sort();
+        assert_eq!(
+            written,
+            [
+                "snapshot-00000000.txt",
+                "snapshot-00000010.txt",
+                "snapshot-00000020.txt"
+            ]
+        );
+
+        // A run picks up where its last snapshot left off.
+        let last = snapshots.join("snapshot-00000020.txt");
+        let text = fs::read_to_string(&last).unwrap();
+        assert!(text.parse::<Snapshot>().is_ok());
+        let resumed = options(&["--steps", "5", last.to_str().unwrap()]).unwrap();
+        run(&resumed).unwrap();
+
+        // The trajectory holds the same steps as the snapshots and replays.
+        let text = fs::read_to_string(&trajectory).unwrap();
+        assert_eq!(text.parse::<Trajectory>().unwrap().frames.len(), 3);
+        let replay = options(&[trajectory.to_str().unwrap()]).unwrap();
+        run(&replay).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_source() {
+        let parsed = options(&["no-such-scenario.txt"]).unwrap();
+        assert!(run(&parsed).unwrap_err().starts_with("Can't read"));
+    }
+}
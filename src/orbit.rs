@@ -0,0 +1,343 @@
+//! Keplerian orbits of one body around another.
+//!
+//! An [`Orbit`](./struct.Orbit.html) describes the path of a body relative to a central body with
+//! gravitational parameter μ = G(M + m). Angles are in radians and measured counter-clockwise from
+//! the x axis of the [`Universe`](../universe/struct.Universe.html)'s coordinate system.
+use std::f64::consts::PI;
+
+use super::*;
+
+/// Keplerian elements of a two-dimensional orbit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Orbit {
+    /// Semi-major axis a. Negative for hyperbolic orbits.
+    pub semi_major_axis: f64,
+    /// Eccentricity e. 0 for circular, below 1 for elliptic and above 1 for hyperbolic orbits.
+    pub eccentricity: f64,
+    /// Argument of periapsis ω, the direction of the closest approach to the central body.
+    pub arg_periapsis: f64,
+    /// True anomaly ν, the angle between periapsis and the body's current position.
+    pub true_anomaly: f64,
+    /// Mean anomaly M, which grows uniformly in time.
+    pub mean_anomaly: f64,
+    /// Whether the body travels clockwise, i.e. has negative angular momentum.
+    pub retrograde: bool,
+}
+
+impl Orbit {
+    /// Compute the elements from a position and velocity relative to the central body.
+    pub fn from_state(pos: Point, vel: Point, mu: f64) -> Self {
+        let r = pos.mag();
+        let v2 = vel.x * vel.x + vel.y * vel.y;
+        let h = cross(pos, vel);
+        let retrograde = h < 0.0;
+
+        // The eccentricity vector points towards periapsis.
+        let r_dot_v = pos.x * vel.x + pos.y * vel.y;
+        let e_vec = (pos * (v2 - mu / r) - vel * r_dot_v) / mu;
+        let eccentricity = e_vec.mag();
+
+        let energy = v2 / 2.0 - mu / r;
+        let semi_major_axis = -mu / (2.0 * energy);
+
+        // Circular orbits have no periapsis, so we measure from the x axis instead.
+        let arg_periapsis = if eccentricity > 1e-12 {
+            e_vec.y.atan2(e_vec.x)
+        } else {
+            0.0
+        };
+
+        let angle = pos.y.atan2(pos.x) - arg_periapsis;
+        let true_anomaly = normalize(if retrograde { -angle } else { angle });
+
+        Self {
+            semi_major_axis,
+            eccentricity,
+            arg_periapsis: normalize(arg_periapsis),
+            true_anomaly,
+            mean_anomaly: mean_from_true(true_anomaly, eccentricity),
+            retrograde,
+        }
+    }
+
+    /// Create an orbit from elements given with a mean anomaly, as they're usually tabulated.
+    pub fn from_mean_anomaly(
+        semi_major_axis: f64,
+        eccentricity: f64,
+        arg_periapsis: f64,
+        mean_anomaly: f64,
+        retrograde: bool,
+    ) -> Self {
+        Self {
+            semi_major_axis,
+            eccentricity,
+            arg_periapsis,
+            true_anomaly: true_from_mean(mean_anomaly, eccentricity),
+            mean_anomaly,
+            retrograde,
+        }
+    }
+
+    /// Compute the elements of a body relative to a central body.
+    pub fn around(pos: Point, vel: Point, center_pos: Point, center_vel: Point, mu: f64) -> Self {
+        Self::from_state(pos - center_pos, vel - center_vel, mu)
+    }
+
+    /// Position and velocity relative to the central body.
+    pub fn to_state(&self, mu: f64) -> (Point, Point) {
+        let e = self.eccentricity;
+        let nu = self.true_anomaly;
+        let p = self.semi_latus_rectum();
+        let r = p / (1.0 + e * nu.cos());
+
+        // In the orbital plane, with periapsis on the x axis.
+        let sign = if self.retrograde { -1.0 } else { 1.0 };
+        let pos = Point::new(r * nu.cos(), sign * r * nu.sin());
+        let vel = Point::new(-nu.sin(), sign * (e + nu.cos())) * (mu / p).sqrt();
+
+        (
+            pos.rotate(self.arg_periapsis),
+            vel.rotate(self.arg_periapsis),
+        )
+    }
+
+    /// Advance the body along its orbit by a time span `dt`.
+    pub fn propagate(&self, dt: f64, mu: f64) -> Self {
+        let mut mean_anomaly = self.mean_anomaly + self.mean_motion(mu) * dt;
+        if self.eccentricity < 1.0 {
+            mean_anomaly = normalize(mean_anomaly);
+        }
+
+        Self {
+            true_anomaly: true_from_mean(mean_anomaly, self.eccentricity),
+            mean_anomaly,
+            ..*self
+        }
+    }
+
+    /// Angular speed n at which the mean anomaly grows.
+    pub fn mean_motion(&self, mu: f64) -> f64 {
+        (mu / self.semi_major_axis.abs().powi(3)).sqrt()
+    }
+
+    /// Time to complete one orbit. Infinite for open orbits.
+    pub fn period(&self, mu: f64) -> f64 {
+        if self.eccentricity < 1.0 {
+            2.0 * PI / self.mean_motion(mu)
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    /// Distance to the central body at its closest approach.
+    pub fn periapsis(&self) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    /// Distance to the central body at its farthest point. Infinite for open orbits.
+    pub fn apoapsis(&self) -> f64 {
+        if self.eccentricity < 1.0 {
+            self.semi_major_axis * (1.0 + self.eccentricity)
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    pub fn is_bound(&self) -> bool {
+        self.eccentricity < 1.0
+    }
+
+    fn semi_latus_rectum(&self) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity * self.eccentricity)
+    }
+}
+
+/// Velocity of a circular orbit at `pos` relative to the central body.
+pub fn circular_velocity(pos: Point, mu: f64, retrograde: bool) -> Point {
+    orbit_velocity(pos, mu, 0.0, retrograde)
+}
+
+/// Velocity of an orbit with eccentricity `e` that has its periapsis at `pos` relative to the
+/// central body.
+pub fn orbit_velocity(pos: Point, mu: f64, e: f64, retrograde: bool) -> Point {
+    let r = pos.mag();
+    let speed = (mu * (1.0 + e) / r).sqrt();
+    let sign = if retrograde { -1.0 } else { 1.0 };
+    Point::new(-pos.y, pos.x) / r * speed * sign
+}
+
+/// Solve Kepler's equation M = E - e sin E for the eccentric anomaly E, or its hyperbolic
+/// counterpart M = e sinh H - H for the hyperbolic anomaly H if `e` > 1, using Newton's method.
+pub fn solve_kepler(mean_anomaly: f64, e: f64) -> f64 {
+    if e < 1.0 {
+        let m = normalize(mean_anomaly);
+        let mut ecc_anomaly = if e < 0.8 { m } else { PI };
+        for _ in 0..50 {
+            let step = (ecc_anomaly - e * ecc_anomaly.sin() - m) / (1.0 - e * ecc_anomaly.cos());
+            ecc_anomaly -= step;
+            if step.abs() < 1e-14 {
+                break;
+            }
+        }
+        ecc_anomaly
+    } else {
+        let m = mean_anomaly;
+        let mut hyp_anomaly = (2.0 * m / e).asinh();
+        for _ in 0..50 {
+            let step = (e * hyp_anomaly.sinh() - hyp_anomaly - m) / (e * hyp_anomaly.cosh() - 1.0);
+            hyp_anomaly -= step;
+            if step.abs() < 1e-14 {
+                break;
+            }
+        }
+        hyp_anomaly
+    }
+}
+
+fn true_from_mean(mean_anomaly: f64, e: f64) -> f64 {
+    let anomaly = solve_kepler(mean_anomaly, e);
+    if e < 1.0 {
+        normalize(
+            2.0 * ((1.0 + e).sqrt() * (anomaly / 2.0).sin())
+                .atan2((1.0 - e).sqrt() * (anomaly / 2.0).cos()),
+        )
+    } else {
+        normalize(2.0 * (((e + 1.0) / (e - 1.0)).sqrt() * (anomaly / 2.0).tanh()).atan())
+    }
+}
+
+fn mean_from_true(true_anomaly: f64, e: f64) -> f64 {
+    if e < 1.0 {
+        let ecc_anomaly = 2.0
+            * ((1.0 - e).sqrt() * (true_anomaly / 2.0).sin())
+                .atan2((1.0 + e).sqrt() * (true_anomaly / 2.0).cos());
+        normalize(ecc_anomaly - e * ecc_anomaly.sin())
+    } else {
+        // Hyperbolic anomalies are signed, negative before periapsis.
+        let nu = if true_anomaly > PI {
+            true_anomaly - 2.0 * PI
+        } else {
+            true_anomaly
+        };
+        let hyp_anomaly = 2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * (nu / 2.0).tan()).atanh();
+        e * hyp_anomaly.sinh() - hyp_anomaly
+    }
+}
+
+/// The z component of the cross product of two vectors in the plane.
+fn cross(a: Point, b: Point) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Wrap an angle into [0, 2π).
+fn normalize(angle: f64) -> f64 {
+    angle.rem_euclid(2.0 * PI)
+}
+
+impl std::fmt::Display for Orbit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{:>13}", "ORBIT")?;
+        writeln!(f, "{:>12}: {}", "Semi-major", self.semi_major_axis)?;
+        writeln!(f, "{:>12}: {}", "Eccentric.", self.eccentricity)?;
+        writeln!(
+            f,
+            "{:>12}: {}",
+            "Arg. peri.",
+            self.arg_periapsis.to_degrees()
+        )?;
+        writeln!(
+            f,
+            "{:>12}: {}",
+            "True anom.",
+            self.true_anomaly.to_degrees()
+        )?;
+        writeln!(
+            f,
+            "{:>12}: {}",
+            "Mean anom.",
+            self.mean_anomaly.to_degrees()
+        )?;
+        writeln!(f, "{:>12}: {}", "Retrograde", self.retrograde)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(a: Point, b: Point) {
+        assert!(a.distance_to(b) < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mu = 4.0 * PI * PI;
+        let states = vec![
+            (Point::new(1.0, 0.0), Point::new(0.0, 2.0 * PI)),
+            (Point::new(0.3, -1.2), Point::new(4.0, 1.5)),
+            (Point::new(-0.7, 0.4), Point::new(3.0, 6.5)),
+            (Point::new(2.0, 1.0), Point::new(-5.0, 8.0)),
+        ];
+
+        for (pos, vel) in states {
+            let orbit = Orbit::from_state(pos, vel, mu);
+            let (p, v) = orbit.to_state(mu);
+            assert_close(p, pos);
+            assert_close(v, vel);
+
+            let from_mean = Orbit::from_mean_anomaly(
+                orbit.semi_major_axis,
+                orbit.eccentricity,
+                orbit.arg_periapsis,
+                orbit.mean_anomaly,
+                orbit.retrograde,
+            );
+            assert!((from_mean.true_anomaly - orbit.true_anomaly).abs() < 1e-9);
+        }
+
+        let orbit = Orbit::from_state(Point::new(0.3, -1.2), Point::new(4.0, 1.5), mu);
+        assert!(orbit.is_bound());
+        let orbit = Orbit::from_state(Point::new(-0.7, 0.4), Point::new(3.0, 6.5), mu);
+        assert!(orbit.retrograde);
+        let orbit = Orbit::from_state(Point::new(2.0, 1.0), Point::new(-5.0, 8.0), mu);
+        assert!(!orbit.is_bound());
+    }
+
+    #[test]
+    fn kepler() {
+        for &e in &[0.0, 0.1, 0.5, 0.9, 0.99] {
+            for i in 0..16 {
+                let m = i as f64 * PI / 8.0;
+                let ecc_anomaly = solve_kepler(m, e);
+                assert!((ecc_anomaly - e * ecc_anomaly.sin() - m).abs() < 1e-12);
+            }
+        }
+
+        let hyp_anomaly = solve_kepler(5.0, 1.5);
+        assert!((1.5 * hyp_anomaly.sinh() - hyp_anomaly - 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn propagate() {
+        let mu = 1.0;
+        let pos = Point::new(1.0, 0.0);
+        let orbit = Orbit::from_state(pos, circular_velocity(pos, mu, false), mu);
+        assert!(orbit.eccentricity < 1e-12);
+        assert!((orbit.period(mu) - 2.0 * PI).abs() < 1e-12);
+
+        let (p, _) = orbit.propagate(PI / 2.0, mu).to_state(mu);
+        assert_close(p, Point::new(0.0, 1.0));
+
+        let orbit = Orbit::from_state(pos, circular_velocity(pos, mu, true), mu);
+        let (p, _) = orbit.propagate(PI / 2.0, mu).to_state(mu);
+        assert_close(p, Point::new(0.0, -1.0));
+
+        // Starting at periapsis, we're back there after a full period.
+        let orbit = Orbit::from_state(pos, orbit_velocity(pos, mu, 0.6, false), mu);
+        assert!((orbit.periapsis() - 1.0).abs() < 1e-12);
+        assert!((orbit.apoapsis() - 4.0).abs() < 1e-9);
+        let (p, _) = orbit.propagate(orbit.period(mu), mu).to_state(mu);
+        assert_close(p, pos);
+    }
+}
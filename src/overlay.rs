@@ -0,0 +1,66 @@
+use super::*;
+
+/// Arrows drawn on top of every planet, showing where it's headed and where it's pulled.
+///
+/// Both kinds of arrow are measured in ticks, so they keep their lengths whatever the units of
+/// the universe.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OverlayConfig {
+    /// Whether to draw each planet's velocity.
+    pub velocities: bool,
+    /// Velocity arrows reach as far as a planet travels in this many ticks at its current
+    /// velocity.
+    pub velocity_scale: f64,
+    /// Whether to draw each planet's net gravitational acceleration.
+    pub accelerations: bool,
+    /// Acceleration arrows reach as far as the acceleration alone would move a planet at rest in
+    /// this many ticks, leaving out the usual half.
+    pub acceleration_scale: f64,
+}
+
+impl OverlayConfig {
+    /// The arrow for `velocity`, in length units, with `dt` time units per tick.
+    pub fn velocity_arrow(&self, velocity: Point, dt: f64) -> Point {
+        velocity * (dt * self.velocity_scale)
+    }
+
+    /// The arrow for `acceleration`, in length units, with `dt` time units per tick.
+    pub fn acceleration_arrow(&self, acceleration: Point, dt: f64) -> Point {
+        acceleration * (dt * self.acceleration_scale).powi(2)
+    }
+}
+
+impl std::default::Default for OverlayConfig {
+    /// No arrows. When shown, velocity arrows are as long as planets would have to be dragged to
+    /// launch them that fast, see [`LAUNCH_TICKS`](../constant.LAUNCH_TICKS.html).
+    fn default() -> Self {
+        Self {
+            velocities: false,
+            velocity_scale: LAUNCH_TICKS,
+            accelerations: false,
+            acceleration_scale: LAUNCH_TICKS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arrows() {
+        let cfg = OverlayConfig {
+            velocity_scale: 10.0,
+            acceleration_scale: 4.0,
+            ..OverlayConfig::default()
+        };
+        assert_eq!(
+            cfg.velocity_arrow(Point::new(1.0, -2.0), 0.5),
+            Point::new(5.0, -10.0)
+        );
+        assert_eq!(
+            cfg.acceleration_arrow(Point::new(1.0, -2.0), 0.5),
+            Point::new(4.0, -8.0)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Massless test particles, pulled by the planets of a
+//! [`Universe`](../universe/struct.Universe.html) without pulling back.
+//!
+//! Particles are kept apart from the planets as [`Tracers`](./struct.Tracers.html), so they
+//! never end up in the quad tree and never merge. That keeps them cheap enough to release
+//! thousands of them to trace the flow of the field, tidal streams or debris.
+use super::*;
+
+/// How test particles move and are drawn. By default, they fly through planets and no
+/// streamlines are drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ParticleConfig {
+    /// Whether particles touching a planet are absorbed by it and removed. Otherwise they fly
+    /// right through.
+    pub absorb: bool,
+    /// Number of past positions drawn behind each particle, tracing its streamline. 0 draws
+    /// none.
+    pub streamlines: usize,
+}
+
+/// Massless tracer particles, with nothing but a position and a velocity each.
+///
+/// Tracers are stored as a structure of arrays rather than one struct per particle, so a hundred
+/// thousand of them stay compact and are moved in tight loops. They're advected by the field of
+/// the massive planets alone: never inserted into the quad tree, they never add to the mass it
+/// aggregates. Tracers are listed in the order they were released, which removing some of them
+/// keeps.
+#[derive(Clone, Debug, Default)]
+pub struct Tracers {
+    positions: Vec<Point>,
+    velocities: Vec<Point>,
+    /// The streamline behind each tracer, only kept while they're recorded.
+    trails: Vec<Trail>,
+}
+
+impl Tracers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Release a tracer at `pos`, moving at `velocity`.
+    pub fn push(&mut self, pos: Point, velocity: Point) {
+        self.positions.push(pos);
+        self.velocities.push(velocity);
+        if !self.trails.is_empty() {
+            self.trails.push(Trail::new());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.velocities.clear();
+        self.trails.clear();
+    }
+
+    pub fn positions(&self) -> &[Point] {
+        &self.positions
+    }
+
+    pub fn velocities(&self) -> &[Point] {
+        &self.velocities
+    }
+
+    /// The streamlines behind the tracers, in the same order. Empty unless they're recorded.
+    pub fn trails(&self) -> &[Trail] {
+        &self.trails
+    }
+
+    /// Kick every tracer by its acceleration, in the order of the tracers, over `dt`.
+    pub fn accelerate(&mut self, accelerations: &[Point], dt: f64) {
+        for (v, a) in self.velocities.iter_mut().zip(accelerations) {
+            *v += *a * dt;
+        }
+    }
+
+    /// Move every tracer along its velocity for a time step of `dt`.
+    pub fn drift(&mut self, dt: f64) {
+        for (p, v) in self.positions.iter_mut().zip(&self.velocities) {
+            *p += *v * dt;
+        }
+    }
+
+    /// Move tracers that left a universe of the given dimensions over an edge back in on the
+    /// opposite side.
+    pub fn wrap(&mut self, dimensions: (f64, f64)) {
+        for p in self.positions.iter_mut() {
+            p.x = p.x.rem_euclid(dimensions.0);
+            p.y = p.y.rem_euclid(dimensions.1);
+        }
+    }
+
+    /// Keep only the tracers whose positions pass `keep`.
+    pub fn retain(&mut self, keep: impl Fn(Point) -> bool) {
+        let kept: Vec<bool> = self.positions.iter().map(|p| keep(*p)).collect();
+        let mut flags = kept.iter();
+        self.positions.retain(|_| *flags.next().unwrap());
+        let mut flags = kept.iter();
+        self.velocities.retain(|_| *flags.next().unwrap());
+        if !self.trails.is_empty() {
+            let mut flags = kept.iter();
+            self.trails.retain(|_| *flags.next().unwrap());
+        }
+    }
+
+    /// Record the current positions in the streamlines, keeping at most `length` of them. A
+    /// `length` of 0 drops the streamlines altogether.
+    pub fn record_trails(&mut self, length: usize) {
+        if length == 0 {
+            self.trails.clear();
+            return;
+        }
+        self.trails.resize_with(self.positions.len(), Trail::new);
+        for (trail, p) in self.trails.iter_mut().zip(&self.positions) {
+            trail.push(*p, length);
+        }
+    }
+}
+
+/// Positions `spacing` apart covering `area`, row by row from its smallest coordinates. The
+/// grid is centered on the area, so it's evenly inset from all edges.
+pub fn grid(area: &Rect, spacing: f64) -> Vec<Point> {
+    if spacing.is_nan() || spacing <= 0.0 {
+        return vec![];
+    }
+    let columns = (area.width() / spacing).floor() as usize;
+    let rows = (area.height() / spacing).floor() as usize;
+    let corner = area.corner(Cardinal::SW);
+    let inset = Point::new(
+        (area.width() - (columns as f64 - 1.0) * spacing) / 2.0,
+        (area.height() - (rows as f64 - 1.0) * spacing) / 2.0,
+    );
+
+    let mut points = Vec::with_capacity(columns * rows);
+    for row in 0..rows {
+        for column in 0..columns {
+            let offset = Point::new(column as f64 * spacing, row as f64 * spacing);
+            points.push(corner + inset + offset);
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn grids() {
+        let points = grid(&Rect::new(50.0, 25.0, 100.0, 50.0), 20.0);
+        assert_eq!(points.len(), 10);
+        assert_eq!(points[0], Point::new(10.0, 15.0));
+        assert_eq!(points[9], Point::new(90.0, 35.0));
+        assert!(grid(&Rect::new(0.0, 0.0, 10.0, 10.0), 0.0).is_empty());
+    }
+
+    #[test]
+    fn tracers() {
+        let mut tracers = Tracers::new();
+        tracers.push(Point::new(95.0, 5.0), Point::new(10.0, -10.0));
+        tracers.push(Point::new(50.0, 50.0), Point::default());
+        tracers.push(Point::new(20.0, 20.0), Point::new(1.0, 0.0));
+        tracers.record_trails(4);
+        assert_eq!(tracers.trails().len(), 3);
+
+        tracers.accelerate(
+            &[Point::default(), Point::new(0.0, 2.0), Point::default()],
+            0.5,
+        );
+        tracers.drift(1.0);
+        tracers.wrap((100.0, 100.0));
+        assert_eq!(tracers.positions()[0], Point::new(5.0, 95.0));
+        assert_eq!(tracers.positions()[1], Point::new(50.0, 51.0));
+
+        // Arrays stay in step when tracers are removed.
+        tracers.retain(|p| p.x > 10.0);
+        assert_eq!(tracers.len(), 2);
+        assert_eq!(tracers.velocities()[0], Point::new(0.0, 1.0));
+        assert_eq!(tracers.trails().len(), 2);
+        assert_eq!(
+            tracers.trails()[1].points().next(),
+            Some(&Point::new(20.0, 20.0))
+        );
+
+        tracers.record_trails(0);
+        assert!(tracers.trails().is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 extern crate rand;
 
 use rand::Rng;
-use std::cell::Cell;
+use std::cell::{Cell, Ref, RefCell};
 use std::f64::consts::PI;
 use uuid::Uuid;
 
@@ -37,15 +37,30 @@ pub struct Planet {
     id: Uuid,
     /// Vector of the planet's current coordinates.
     pos: Cell<Point>,
-    /// Density D in kg/m³.
+    /// Density D in mass units per cubic length unit of the [`UnitSystem`](../units/struct.UnitSystem.html),
+    /// i.e. kg/m³ in SI.
     density: Cell<f64>,
-    /// Radius r in m.
+    /// Radius r in length units.
     radius: Cell<f64>,
     /// The vector at which the planet will travel on the next
     /// [`update`](../planet/struct.Planet.html#method.update).
     velocity: Cell<Point>,
     /// Marks the planet to be removed from [`Universe`](../universe/struct.Universe.html)'s `planets`.
     dead: Cell<bool>,
+    /// The planet's past positions.
+    trail: RefCell<Trail>,
+    /// Time since the planet was created, in time units.
+    age: Cell<f64>,
+    /// Number of planets this one has eaten, including those they had eaten themselves.
+    merges: Cell<u32>,
+    /// The planet's own colour, if it has one.
+    color: Cell<Option<Color>>,
+    /// Whether the planet is a sun, which is drawn to stand out.
+    sun: bool,
+    /// Whether the planet is held in place, neither accelerating nor moving.
+    pinned: Cell<bool>,
+    /// Net gravitational acceleration the planet felt when forces were last worked out.
+    acceleration: Cell<Point>,
 }
 
 #[allow(dead_code, non_snake_case)]
@@ -59,11 +74,75 @@ impl Planet {
             pos: Cell::new(Point { x, y }),
             velocity: Cell::new(velocity),
             dead: Cell::new(false),
+            trail: RefCell::new(Trail::new()),
+            age: Cell::new(0.0),
+            merges: Cell::new(0),
+            color: Cell::new(None),
+            sun: false,
+            pinned: Cell::new(false),
+            acceleration: Cell::new(Point::default()),
         }
     }
 
+    /// Create a `Planet` of a given mass, deriving its density from mass and radius.
+    pub fn with_mass(pos: Point, mass: f64, radius: f64, velocity: Point) -> Self {
+        let volume = 4.0 / 3.0 * PI * radius.powf(3.0);
+        Self::new(pos.x, pos.y, mass / volume, radius, velocity)
+    }
+
     pub fn new_sun(x: f64, y: f64) -> Self {
-        Self::new(x, y, 6_000.0, 20.0, Point::new(0.0, 0.0))
+        Self::new(x, y, 6_000.0, 20.0, Point::new(0.0, 0.0)).into_sun()
+    }
+
+    /// Turn the `Planet` into a sun, coloured and drawn to stand out. Suns are pinned in place.
+    pub fn into_sun(self) -> Self {
+        self.pin(true);
+        Self {
+            sun: true,
+            ..self.with_color(Color::SUN)
+        }
+    }
+
+    /// Give the `Planet` its own colour.
+    pub fn with_color(self, color: Color) -> Self {
+        self.color.set(Some(color));
+        self
+    }
+
+    /// Recreate a `Planet` from the state captured in a
+    /// [`Snapshot`](../snapshot/struct.Snapshot.html).
+    pub fn from_state(state: &PlanetState) -> Self {
+        Planet {
+            id: state.id,
+            pos: Cell::new(state.pos),
+            density: Cell::new(state.density),
+            radius: Cell::new(state.radius),
+            velocity: Cell::new(state.velocity),
+            dead: Cell::new(false),
+            trail: RefCell::new(Trail::new()),
+            age: Cell::new(state.age),
+            merges: Cell::new(state.merges),
+            color: Cell::new(state.color),
+            sun: state.sun,
+            pinned: Cell::new(state.pinned),
+            acceleration: Cell::new(Point::default()),
+        }
+    }
+
+    /// Capture the planet's state, leaving out its trail.
+    pub fn state(&self) -> PlanetState {
+        PlanetState {
+            id: self.id,
+            pos: self.pos(),
+            velocity: self.velocity(),
+            density: self.density(),
+            radius: self.radius(),
+            age: self.age(),
+            merges: self.merges(),
+            pinned: self.pinned(),
+            sun: self.sun,
+            color: self.color(),
+        }
     }
 
     /// Create a `Planet` with randomly generated parameters.
@@ -83,14 +162,7 @@ impl Planet {
             y: rng.gen_range(-0.05, 0.05),
         };
 
-        Planet {
-            id: Uuid::new_v4(),
-            density: Cell::new(density),
-            radius: Cell::new(radius),
-            pos: Cell::new(pos),
-            velocity: Cell::new(velocity),
-            dead: Cell::new(false),
-        }
+        Self::new(pos.x, pos.y, density, radius, velocity)
     }
 
     /// Create a `Planet` with randomly generated parameters at a specified position.
@@ -105,24 +177,29 @@ impl Planet {
             y: rng.gen_range(-0.05, 0.05),
         };
 
-        Planet {
-            id: Uuid::new_v4(),
-            density: Cell::new(density),
-            radius: Cell::new(radius),
-            pos: Cell::new(Point { x, y }),
-            velocity: Cell::new(velocity),
-            dead: Cell::new(false),
-        }
+        Self::new(x, y, density, radius, velocity)
     }
 
     /// Update the planet's position by adding its velocity to its current position.
     /// If it moves out of the universe's dimensions, it's inserted back in on the other side.
     pub fn update(&self, dimensions: (f64, f64)) {
+        self.advance(1.0, dimensions);
+    }
+
+    /// Move the planet along its velocity for a time step of `dt`, with no regard for the edges of
+    /// the universe.
+    pub fn drift(&self, dt: f64) {
+        self.pos.set(self.pos() + self.velocity() * dt);
+    }
+
+    /// Move the planet along its velocity for a time step of `dt`, wrapping around the universe's
+    /// dimensions like [`update`](#method.update).
+    pub fn advance(&self, dt: f64, dimensions: (f64, f64)) {
         let max_x = dimensions.0;
         let max_y = dimensions.1;
 
-        let mut x = self.pos().x + self.velocity().x;
-        let mut y = self.pos().y + self.velocity().y;
+        let mut x = self.pos().x + self.velocity().x * dt;
+        let mut y = self.pos().y + self.velocity().y * dt;
 
         if x > max_x {
             x -= max_x;
@@ -139,29 +216,138 @@ impl Planet {
         self.pos.set(Point { x, y });
     }
 
-    /// Add two masses together, calculate the new volume and derive a new radius.  
-    /// V = m/D  
-    /// V = 4 / 3 * π * radius³  
+    /// Add two masses and volumes together, derive a new radius and take on the density of the
+    /// two combined, so no mass is made or lost whatever their densities.  
+    /// V = V₁ + V₂  
+    /// D = (m₁ + m₂) / V  
     /// r³ = V / (4 / 3 * π)  
+    ///
+    /// The merged planet carries on the trail of the heavier of the two. Unless it's pinned, it
+    /// moves to the common center of mass, with the combined momentum of both.
     pub fn eat(&self, other_p: &Planet) {
+        if other_p.mass() > self.mass() {
+            self.trail.swap(&other_p.trail);
+        }
+        self.merges.set(self.merges() + other_p.merges() + 1);
+
         let m = self.mass() + other_p.mass();
-        let D = (self.density() + other_p.density()) / 2.0;
-        let V = m / D;
+        if !self.pinned() {
+            let (m1, m2) = (self.mass(), other_p.mass());
+            self.pos.set((self.pos() * m1 + other_p.pos() * m2) / m);
+            self.velocity
+                .set((self.velocity() * m1 + other_p.velocity() * m2) / m);
+        }
+        let V = self.volume() + other_p.volume();
+        self.density.set(m / V);
 
         let r = (V / (4.0 / 3.0 * PI)).cbrt();
         self.radius.set(r);
     }
 
-    /// Add a given acceleration to the planet's velocity. The acceleration vector
-    /// should represent the single net force to be applied each tick.
+    /// Add a given change in velocity to the planet's velocity. The vector should represent the
+    /// single net acceleration to be applied each tick, multiplied by the tick's time step.
     pub fn accelerate(&self, acc: Point) {
         self.velocity.set(self.velocity.get() + acc);
     }
 
+    /// The net gravitational acceleration on the planet when the forces were last worked out,
+    /// whether it's pinned or not. Zero until the planet has been through a
+    /// [`tick`](../universe/struct.Universe.html#method.tick).
+    pub fn acceleration(&self) -> Point {
+        self.acceleration.get()
+    }
+
+    pub fn set_acceleration(&self, acc: Point) {
+        self.acceleration.set(acc);
+    }
+
+    /// Compute the `Planet`'s current [`Orbit`](../orbit/struct.Orbit.html) around a central
+    /// body, given the gravitational constant `g`.
+    pub fn orbit_around(&self, central: &Planet, g: f64) -> Orbit {
+        let mu = g * (self.mass() + central.mass());
+        Orbit::around(
+            self.pos(),
+            self.velocity(),
+            central.pos(),
+            central.velocity(),
+            mu,
+        )
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
     pub fn pos(&self) -> Point {
         self.pos.get()
     }
 
+    /// Take over the trail of `previous`, e.g. an earlier state of the same planet.
+    pub fn carry_on(&self, previous: &Planet) {
+        self.trail.swap(&previous.trail);
+    }
+
+    /// Record the current position in the planet's trail, keeping at most `length` positions.
+    pub fn record_trail(&self, length: usize) {
+        if length == 0 {
+            self.trail.borrow_mut().clear();
+        } else {
+            self.trail.borrow_mut().push(self.pos(), length);
+        }
+    }
+
+    pub fn trail(&self) -> Ref<'_, Trail> {
+        self.trail.borrow()
+    }
+
+    /// Let time `dt` pass for the planet.
+    pub fn grow_older(&self, dt: f64) {
+        self.age.set(self.age() + dt);
+    }
+
+    pub fn age(&self) -> f64 {
+        self.age.get()
+    }
+
+    pub fn merges(&self) -> u32 {
+        self.merges.get()
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        self.color.get()
+    }
+
+    pub fn set_color(&self, color: Option<Color>) {
+        self.color.set(color);
+    }
+
+    /// Hold the planet in place or let it go again.
+    pub fn pin(&self, pinned: bool) {
+        self.pinned.set(pinned);
+    }
+
+    pub fn pinned(&self) -> bool {
+        self.pinned.get()
+    }
+
+    /// Change the planet's mass, keeping its density and deriving a new radius.
+    pub fn set_mass(&self, mass: f64) {
+        let r = (mass / self.density() / (4.0 / 3.0 * PI)).cbrt();
+        self.radius.set(r);
+    }
+
+    pub fn set_pos(&self, pos: Point) {
+        self.pos.set(pos);
+    }
+
+    pub fn set_velocity(&self, velocity: Point) {
+        self.velocity.set(velocity);
+    }
+
+    pub fn is_sun(&self) -> bool {
+        self.sun
+    }
+
     pub fn dead(&self) -> bool {
         self.dead.get()
     }
@@ -178,7 +364,7 @@ impl Planet {
         self.density() * self.volume()
     }
 
-    fn density(&self) -> f64 {
+    pub fn density(&self) -> f64 {
         self.density.get()
     }
 
@@ -204,6 +390,10 @@ impl Newtonian for Planet {
         self.pos.get()
     }
 
+    fn radius(&self) -> f64 {
+        self.radius.get()
+    }
+
     fn velocity(&self) -> Point {
         self.velocity.get()
     }
@@ -232,6 +422,7 @@ impl std::fmt::Display for Planet {
         )?;
         writeln!(f, "{:>12}: {}", "Magnitude", self.velocity().mag())?;
         writeln!(f, "{:>12}: {}", "Dead", self.dead())?;
+        writeln!(f, "{:>12}: {}", "Pinned", self.pinned())?;
         writeln!(f, "{:>12}: {}", "Volume", self.volume())?;
         writeln!(f, "{:>12}: {}", "Mass", self.mass())?;
         Ok(())
@@ -263,4 +454,30 @@ mod test {
         // Run `cargo test -- --nocapture` to see `println!` output.
         //println!("{}", p1);
     }
+
+    #[test]
+    fn merges() {
+        let v = Point::new(1.0, 1.0);
+        let p1 = Planet::new(10.0, 10.0, 1000.0, 10.0, v);
+
+        // The bigger planet's trail lives on.
+        let p2 = Planet::new(20.0, 20.0, 1000.0, 20.0, v);
+        p1.record_trail(10);
+        p2.record_trail(10);
+        p2.record_trail(10);
+        p1.eat(&p2);
+        assert_eq!(p1.trail().len(), 2);
+        assert_eq!(p1.merges(), 1);
+
+        // Mass is kept when planets of different densities merge.
+        let sun = Planet::new_sun(0.0, 0.0);
+        let (m1, m2) = (sun.mass(), p1.mass());
+        sun.eat(&p1);
+        assert!((sun.mass() - (m1 + m2)).abs() < 1e-9 * sun.mass());
+
+        let (mass, radius) = (p1.mass(), p1.radius());
+        p1.set_mass(mass * 8.0);
+        assert!((p1.radius() - 2.0 * radius).abs() < 1e-9);
+        assert_eq!(p1.density(), 1000.0);
+    }
 }
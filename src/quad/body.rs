@@ -7,6 +7,7 @@ pub struct Body {
     center: Point,
     id: Uuid,
     mass: f64,
+    radius: f64,
     velocity: Point,
 }
 
@@ -16,9 +17,15 @@ impl Body {
             center,
             id,
             mass,
+            radius: 0.0,
             velocity: Point::new(0.0, 0.0),
         }
     }
+
+    /// Give the body a size, it's a point mass otherwise.
+    pub fn with_radius(self, radius: f64) -> Self {
+        Self { radius, ..self }
+    }
 }
 
 impl Newtonian for Body {
@@ -34,6 +41,10 @@ impl Newtonian for Body {
         self.center
     }
 
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
     fn velocity(&self) -> Point {
         self.velocity
     }
@@ -54,6 +65,7 @@ impl std::fmt::Display for Body {
         writeln!(f, "{:>13}", "BODY")?;
         writeln!(f, "{:>12}: {}", "Id", self.id())?;
         writeln!(f, "{:>12}: {}", "Mass", self.mass())?;
+        writeln!(f, "{:>12}: {}", "Radius", self.radius())?;
         writeln!(f, "{:>12}: x: {}, y: {}", "Pos", p.x, p.y)?;
         writeln!(f, "{:>12}: x: {}, y: {}", "Velocity", v.x, v.y)?;
         Ok(())
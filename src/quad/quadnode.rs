@@ -2,6 +2,8 @@
 // http://arborjs.org/docs/barnes-hut and
 // https://www.cs.princeton.edu/courses/archive/fall03/cs126/assignments/nbody.html
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::rc::Rc;
 use uuid::Uuid;
 
@@ -14,6 +16,10 @@ pub trait Newtonian {
     fn mass(&self) -> f64;
     fn position(&self) -> Point;
     fn velocity(&self) -> Point;
+    /// Size of the body, used when picking it. Point masses have none.
+    fn radius(&self) -> f64 {
+        0.0
+    }
     fn set_position(&mut self, new_position: Point);
     fn set_velocity(&mut self, new_velocity: Point);
 }
@@ -25,10 +31,17 @@ pub trait Newtonian {
 
 pub type QuadBody = Rc<RefCell<dyn Newtonian>>;
 
+/// Depth at which nodes stop subdividing and hold any number of bodies, so bodies sharing the
+/// same position don't send us down the tree forever.
+const MAX_DEPTH: usize = 32;
+
 /// Shared config that applies to all nodes in the tree.
 pub struct QuadConfig {
     pub capacity: usize,
     pub theta: f64,
+    /// Plummer softening length ε. Bodies pull as if they were `sqrt(d² + ε²)` away, so close
+    /// encounters stay smooth. 0 is plain Newtonian gravity.
+    pub softening: f64,
 }
 
 /// Used to construct a quad tree. Ether holds a vector of bodies up until its capacity or aggregates the mass and
@@ -54,10 +67,93 @@ pub struct QuadNode {
     nodes: Option<Box<[Self; 4]>>,
     /// A [`Rect`](../rect/struct.Rect.html) representing the Cartesian plane this node covers.
     rect: Rect,
+    /// Number of nodes above this one, 0 for the root.
+    depth: usize,
+    /// The area actually covered by all contained bodies and their radii. Usually within `rect`,
+    /// but bodies outside of the tree's bounds stick out of it.
+    extent: Option<Rect>,
+}
+
+/// A read-only view on a single node of the tree, as yielded by [`iter`](./struct.QuadNode.html#method.iter).
+#[derive(Debug)]
+pub struct NodeView<'a> {
+    pub rect: &'a Rect,
+    /// Aggregated mass of all bodies in or below the node.
+    pub mass: f64,
+    /// Center of mass of all bodies in or below the node.
+    pub com: Point,
+    pub depth: usize,
+    /// Number of bodies held directly by the node, always 0 for internal nodes.
+    pub bodies: usize,
+    pub internal: bool,
+}
+
+/// How a node is treated when summing up the forces on a body.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Interaction {
+    /// The node is far enough away for its aggregated mass to stand in for its bodies.
+    Approximated,
+    /// The node is too close to be approximated, so its sub-nodes are looked at instead.
+    Opened,
+    /// The node is external, so its bodies are applied one by one.
+    Direct,
+}
+
+/// A node or body waiting to be looked at in [`nearest`](./struct.QuadNode.html#method.nearest),
+/// ordered so the closest one is popped off the heap first.
+struct Candidate<'a> {
+    distance: f64,
+    item: Item<'a>,
+}
+
+enum Item<'a> {
+    Node(&'a QuadNode),
+    Body(QuadBody),
+}
+
+impl PartialEq for Candidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate<'_> {}
+
+impl PartialOrd for Candidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+/// Depth-first iterator over all nodes of a tree, see [`iter`](./struct.QuadNode.html#method.iter).
+pub struct Iter<'a> {
+    stack: Vec<&'a QuadNode>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = NodeView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if let Some(nodes) = &node.nodes {
+            self.stack.extend(nodes.iter().rev());
+        }
+        Some(node.view())
+    }
 }
 
 impl QuadNode {
     pub fn new(cfg: Rc<QuadConfig>, rect: Rect) -> Self {
+        Self::with_depth(cfg, rect, 0)
+    }
+
+    fn with_depth(cfg: Rc<QuadConfig>, rect: Rect, depth: usize) -> Self {
         let com = rect.center();
         Self {
             cfg,
@@ -66,6 +162,292 @@ impl QuadNode {
             bodies: Vec::new(),
             mass: None,
             nodes: None,
+            depth,
+            extent: None,
+        }
+    }
+
+    /// Iterate over this node and all nodes below it, depth-first.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { stack: vec![self] }
+    }
+
+    /// List the nodes looked at when summing up the forces on a body at `target`, and how each of
+    /// them is treated at the configured `theta`. Bodies with the given `id` are left out, so a
+    /// body isn't attracted by itself.
+    pub fn interactions(&self, target: Point, id: Uuid) -> Vec<(NodeView<'_>, Interaction)> {
+        let mut interactions = vec![];
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            if node.mass.is_none() {
+                continue;
+            }
+            match &node.nodes {
+                Some(nodes) => {
+                    if node.is_far_from(target) {
+                        interactions.push((node.view(), Interaction::Approximated));
+                    } else {
+                        interactions.push((node.view(), Interaction::Opened));
+                        stack.extend(nodes.iter().rev());
+                    }
+                }
+                None => {
+                    if node.bodies.iter().any(|b| b.borrow().id() != id) {
+                        interactions.push((node.view(), Interaction::Direct));
+                    }
+                }
+            }
+        }
+
+        interactions
+    }
+
+    /// Acceleration the bodies in the tree cause on a body at `target`, for a gravitational
+    /// constant of `g`. Nodes far enough away at the configured `theta` pull as a whole from their
+    /// center of mass, bodies with the given `id` are left out.
+    pub fn acceleration(&self, target: Point, id: Uuid, g: f64) -> Point {
+        self.acceleration_with(target, id, g, &mut vec![])
+    }
+
+    /// Acceleration the bodies in the tree cause at each of `targets`, like
+    /// [`acceleration`](#method.acceleration) but without leaving anyone out. Meant for many
+    /// massless tracers evaluated against a tree of massive bodies, so the traversal's stack is
+    /// shared between them.
+    pub fn accelerations(&self, targets: &[Point], g: f64) -> Vec<Point> {
+        let mut stack = vec![];
+        targets
+            .iter()
+            .map(|target| self.acceleration_with(*target, Uuid::nil(), g, &mut stack))
+            .collect()
+    }
+
+    fn acceleration_with<'a>(
+        &'a self,
+        target: Point,
+        id: Uuid,
+        g: f64,
+        stack: &mut Vec<&'a QuadNode>,
+    ) -> Point {
+        let epsilon = self.cfg.softening;
+        let pull = |pos: Point, mass: f64| {
+            let direction = pos - target;
+            let d = (direction.mag().powi(2) + epsilon * epsilon).sqrt();
+            if d == 0.0 {
+                Point::default()
+            } else {
+                direction * (g * mass / (d * d * d))
+            }
+        };
+
+        let mut acc = Point::default();
+        stack.clear();
+        stack.push(self);
+        while let Some(node) = stack.pop() {
+            if node.mass.is_none() {
+                continue;
+            }
+            match &node.nodes {
+                Some(nodes) => {
+                    if node.is_far_from(target) {
+                        acc += pull(node.com, node.mass());
+                    } else {
+                        stack.extend(nodes.iter());
+                    }
+                }
+                // Nobody is left out, and a single body is all there is to the node.
+                None if id.is_nil() && node.bodies.len() == 1 => {
+                    acc += pull(node.com, node.mass());
+                }
+                None => {
+                    for body in &node.bodies {
+                        let body = body.borrow();
+                        if body.id() != id {
+                            acc += pull(body.position(), body.mass());
+                        }
+                    }
+                }
+            }
+        }
+
+        acc
+    }
+
+    /// Gravitational potential the bodies in the tree cause at `target`, for a gravitational
+    /// constant of `g`. Nodes are approximated like in [`acceleration`](#method.acceleration).
+    /// Inside a body, its potential is taken as at its surface, so it stays finite.
+    pub fn potential(&self, target: Point, g: f64) -> f64 {
+        let softened = |d: f64| (d * d + self.cfg.softening.powi(2)).sqrt();
+        let mut potential = 0.0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if node.mass.is_none() {
+                continue;
+            }
+            match &node.nodes {
+                Some(nodes) => {
+                    if node.is_far_from(target) {
+                        potential -= g * node.mass() / softened((node.com - target).mag());
+                    } else {
+                        stack.extend(nodes.iter());
+                    }
+                }
+                None => {
+                    for body in &node.bodies {
+                        let body = body.borrow();
+                        let d = softened((body.position() - target).mag()).max(body.radius());
+                        if d > 0.0 {
+                            potential -= g * body.mass() / d;
+                        }
+                    }
+                }
+            }
+        }
+
+        potential
+    }
+
+    /// Find all bodies positioned inside of `area`, including its edges.
+    pub fn query(&self, area: &Rect) -> Vec<QuadBody> {
+        self.collect(
+            |extent| extent.intersects(area),
+            |body| area.covers(&body.position()),
+        )
+    }
+
+    /// Find all bodies positioned within `radius` of `center`.
+    pub fn query_circle(&self, center: Point, radius: f64) -> Vec<QuadBody> {
+        self.collect(
+            |extent| extent.distance_to(center) <= radius,
+            |body| body.position().distance_to(center) <= radius,
+        )
+    }
+
+    /// Find the `k` bodies closest to `p`, closest first.
+    ///
+    /// Nodes are searched best-first, ordered by how close they could possibly hold a body, so
+    /// only nodes that might hold one of the `k` closest are ever opened.
+    pub fn nearest(&self, p: Point, k: usize) -> Vec<QuadBody> {
+        let mut found = vec![];
+        let mut queue = BinaryHeap::new();
+        if let Some(extent) = &self.extent {
+            queue.push(Candidate {
+                distance: extent.distance_to(p),
+                item: Item::Node(self),
+            });
+        }
+
+        while found.len() < k {
+            let candidate = match queue.pop() {
+                Some(candidate) => candidate,
+                None => break,
+            };
+            match candidate.item {
+                Item::Body(body) => found.push(body),
+                Item::Node(node) => {
+                    for body in &node.bodies {
+                        queue.push(Candidate {
+                            distance: body.borrow().position().distance_to(p),
+                            item: Item::Body(body.clone()),
+                        });
+                    }
+                    for child in node.nodes.iter().flat_map(|nodes| nodes.iter()) {
+                        if let Some(extent) = &child.extent {
+                            queue.push(Candidate {
+                                distance: extent.distance_to(p),
+                                item: Item::Node(child),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Find the body covering `p`, like a click on it. Bodies smaller than `tolerance` are
+    /// picked as if they had that radius. Where bodies overlap, the one whose edge is furthest
+    /// away from `p` wins.
+    pub fn pick(&self, p: Point, tolerance: f64) -> Option<QuadBody> {
+        let gap =
+            |body: &dyn Newtonian| body.position().distance_to(p) - body.radius().max(tolerance);
+        self.collect(
+            |extent| extent.distance_to(p) <= tolerance,
+            |body| gap(body) <= 0.0,
+        )
+        .into_iter()
+        .min_by(|a, b| gap(&*a.borrow()).total_cmp(&gap(&*b.borrow())))
+    }
+
+    /// Collect the bodies matching `accept`, only looking into nodes whose extent passes
+    /// `visit`.
+    fn collect(
+        &self,
+        visit: impl Fn(&Rect) -> bool,
+        accept: impl Fn(&dyn Newtonian) -> bool,
+    ) -> Vec<QuadBody> {
+        let mut found = vec![];
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            match &node.extent {
+                Some(extent) if visit(extent) => {}
+                _ => continue,
+            }
+            match &node.nodes {
+                Some(nodes) => stack.extend(nodes.iter()),
+                None => found.extend(node.bodies.iter().filter(|b| accept(&*b.borrow())).cloned()),
+            }
+        }
+
+        found
+    }
+
+    pub fn rect(&self) -> &Rect {
+        &self.rect
+    }
+
+    pub fn mass(&self) -> f64 {
+        self.mass.unwrap_or(0.0)
+    }
+
+    pub fn com(&self) -> Point {
+        self.com
+    }
+
+    fn view(&self) -> NodeView<'_> {
+        NodeView {
+            rect: &self.rect,
+            mass: self.mass(),
+            com: self.com,
+            depth: self.depth,
+            bodies: self.bodies.len(),
+            internal: self.nodes.is_some(),
+        }
+    }
+
+    /// Whether the node is far enough away from `target` for its aggregated values to be used,
+    /// that is if the ratio of its size to its distance is below `theta`. Nodes reaching over
+    /// `target` never are, or a body would pull on itself.
+    fn is_far_from(&self, target: Point) -> bool {
+        if self.extent.is_some_and(|extent| extent.covers(&target)) {
+            return false;
+        }
+        let s = (self.rect.width() + self.rect.height()) / 2.0;
+        let d = target.distance_to(self.com);
+        s / d < self.cfg.theta
+    }
+
+    /// Index of the sub-node covering `p`, in the order they're created by
+    /// [`subdivide`](#method.subdivide). Positions outside of the node go to the closest one.
+    fn quadrant(&self, p: Point) -> usize {
+        let c = self.rect.center();
+        match (p.x < c.x, p.y < c.y) {
+            (true, false) => 0,
+            (false, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
         }
     }
 
@@ -74,7 +456,9 @@ impl QuadNode {
         self.aggregate(body.clone());
 
         // If we're still an external node and we're not at capacity yet, we insert and return.
-        if self.nodes.is_none() && self.bodies.len() < self.cfg.capacity {
+        if self.nodes.is_none()
+            && (self.bodies.len() < self.cfg.capacity || self.depth >= MAX_DEPTH)
+        {
             self.bodies.push(body);
             return Ok(());
         }
@@ -92,12 +476,8 @@ impl QuadNode {
 
         // All bodies are then recursively passed on to our sub-nodes.
         for body in bodies {
-            for node in self.nodes.as_mut().unwrap().iter_mut() {
-                if node.rect.contains(&body.borrow().position()) {
-                    node.insert(body)?;
-                    break;
-                }
-            }
+            let i = self.quadrant(body.borrow().position());
+            self.nodes.as_mut().unwrap()[i].insert(body)?;
         }
 
         Ok(())
@@ -132,6 +512,7 @@ impl QuadNode {
                 }
 
                 // We keep going recursively.
+                #[allow(clippy::never_loop)]
                 for node in nodes.iter() {
                     return node.sum_up_force(target_body.clone(), delta, net_v);
                 }
@@ -219,37 +600,50 @@ impl QuadNode {
     }
 
     fn subdivide(&mut self) {
+        let depth = self.depth + 1;
         self.nodes = Some(Box::new([
-            Self::new(self.cfg.clone(), self.rect.split_rect(Cardinal::NW)),
-            Self::new(self.cfg.clone(), self.rect.split_rect(Cardinal::NE)),
-            Self::new(self.cfg.clone(), self.rect.split_rect(Cardinal::SE)),
-            Self::new(self.cfg.clone(), self.rect.split_rect(Cardinal::SW)),
+            Self::with_depth(self.cfg.clone(), self.rect.split_rect(Cardinal::NW), depth),
+            Self::with_depth(self.cfg.clone(), self.rect.split_rect(Cardinal::NE), depth),
+            Self::with_depth(self.cfg.clone(), self.rect.split_rect(Cardinal::SE), depth),
+            Self::with_depth(self.cfg.clone(), self.rect.split_rect(Cardinal::SW), depth),
         ]));
     }
 
+    /// Add a body to the mass, center of mass and extent of the node. Massless bodies, like
+    /// tracers, take up space but are never added to the mass, so they don't pull at anything.
     fn aggregate(&mut self, body: QuadBody) {
         let body = body.borrow();
-        let (new_mass, new_x, new_y) = match self.mass {
-            Some(mass) => {
-                let new_mass = mass + body.mass();
-                (
-                    new_mass,
-                    (self.com.x * mass + body.position().x * body.mass()) / new_mass,
-                    (self.com.y * mass + body.position().y * body.mass()) / new_mass,
-                )
-            }
-            None => (body.mass(), body.position().x, body.position().y),
-        };
+        if body.mass() > 0.0 {
+            let (new_mass, new_x, new_y) = match self.mass {
+                Some(mass) => {
+                    let new_mass = mass + body.mass();
+                    (
+                        new_mass,
+                        (self.com.x * mass + body.position().x * body.mass()) / new_mass,
+                        (self.com.y * mass + body.position().y * body.mass()) / new_mass,
+                    )
+                }
+                None => (body.mass(), body.position().x, body.position().y),
+            };
+
+            self.mass = Some(new_mass);
+            self.com = Point::new(new_x, new_y);
+        }
 
-        self.mass = Some(new_mass);
-        self.com = Point::new(new_x, new_y);
+        let r = body.radius();
+        let covered = Rect::new(body.position().x, body.position().y, 2.0 * r, 2.0 * r);
+        self.extent = Some(match &self.extent {
+            Some(extent) => extent.union(&covered),
+            None => covered,
+        });
     }
 }
 
 #[cfg(test)]
-
 mod test {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     fn setupdate_body() -> (QuadNode, Vec<Rc<RefCell<Body>>>) {
         let width = 10.00;
@@ -259,6 +653,7 @@ mod test {
         let cfg = Rc::new(QuadConfig {
             capacity: 1,
             theta: 0.5,
+            softening: 0.0,
         });
 
         let mut bodies = vec![];
@@ -277,6 +672,7 @@ mod test {
         (QuadNode::new(cfg, bounds), bodies)
     }
 
+    #[allow(clippy::bool_assert_comparison)]
     #[test]
     fn subdivide_and_aggregate() {
         let (mut qnode, bodies) = setupdate_body();
@@ -310,6 +706,200 @@ mod test {
         assert_eq!(l2_sw.bodies.len(), 1);
     }
 
+    #[test]
+    fn traversal() {
+        let (mut qnode, bodies) = setupdate_body();
+        for b in &bodies {
+            qnode.insert(b.clone()).unwrap();
+        }
+
+        // Root, its four quadrants and another four below NE.
+        let views: Vec<NodeView> = qnode.iter().collect();
+        assert_eq!(views.len(), 9);
+        assert_eq!(views[0].depth, 0);
+        assert_eq!(views[0].mass, 400.0);
+        assert_eq!(views.iter().filter(|v| v.bodies > 0).count(), 4);
+        assert_eq!(views.iter().map(|v| v.depth).max(), Some(2));
+
+        // Seen from far away, the whole tree is approximated.
+        let far = qnode.interactions(Point::new(1000.0, 1000.0), Uuid::new_v4());
+        assert_eq!(far.len(), 1);
+        assert_eq!(far[0].1, Interaction::Approximated);
+
+        // Up close, everything is opened until we reach the other bodies.
+        let b1 = bodies[0].borrow();
+        let close = qnode.interactions(b1.position(), b1.id());
+        let direct = close
+            .iter()
+            .filter(|(_, i)| *i == Interaction::Direct)
+            .count();
+        assert_eq!(direct, 3);
+        assert_eq!(close[0].1, Interaction::Opened);
+    }
+
+    #[test]
+    fn queries() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let (mut qnode, _) = setupdate_body();
+        let mut bodies: Vec<QuadBody> = vec![];
+        for _ in 0..300 {
+            // Some bodies stray outside of the tree's bounds.
+            let pos = Point::new(rng.gen_range(-2.0, 12.0), rng.gen_range(-2.0, 12.0));
+            let body = Body::new(Uuid::new_v4(), pos, 1.0).with_radius(rng.gen_range(0.0, 0.5));
+            let body: QuadBody = Rc::new(RefCell::new(body));
+            qnode.insert(body.clone()).unwrap();
+            bodies.push(body);
+        }
+
+        let ids = |found: Vec<QuadBody>| {
+            let mut ids: Vec<Uuid> = found.iter().map(|b| b.borrow().id()).collect();
+            ids.sort();
+            ids
+        };
+        let scan = |accept: &dyn Fn(&dyn Newtonian) -> bool| {
+            ids(bodies
+                .iter()
+                .filter(|b| accept(&*b.borrow()))
+                .cloned()
+                .collect())
+        };
+
+        for i in 0..50 {
+            let p = match i % 2 {
+                0 => Point::new(rng.gen_range(-3.0, 13.0), rng.gen_range(-3.0, 13.0)),
+                _ => bodies[i].borrow().position(),
+            };
+
+            let area = Rect::new(p.x, p.y, rng.gen_range(0.0, 5.0), rng.gen_range(0.0, 5.0));
+            let expected = scan(&|b| area.covers(&b.position()));
+            assert_eq!(ids(qnode.query(&area)), expected);
+
+            let r = rng.gen_range(0.0, 3.0);
+            let expected = scan(&|b| b.position().distance_to(p) <= r);
+            assert_eq!(ids(qnode.query_circle(p, r)), expected);
+
+            let distance = |b: &QuadBody| b.borrow().position().distance_to(p);
+            let mut expected: Vec<f64> = bodies.iter().map(distance).collect();
+            expected.sort_by(f64::total_cmp);
+            let found: Vec<f64> = qnode.nearest(p, 5).iter().map(distance).collect();
+            assert_eq!(found, expected[..5]);
+
+            let gap = |b: &QuadBody| {
+                let b = b.borrow();
+                b.position().distance_to(p) - b.radius().max(0.1)
+            };
+            let expected = bodies
+                .iter()
+                .filter(|b| gap(b) <= 0.0)
+                .min_by(|a, b| gap(a).total_cmp(&gap(b)))
+                .map(|b| b.borrow().id());
+            assert_eq!(qnode.pick(p, 0.1).map(|b| b.borrow().id()), expected);
+        }
+
+        assert_eq!(qnode.nearest(Point::new(0.0, 0.0), 1000).len(), 300);
+    }
+
+    #[test]
+    fn potential() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let (mut qnode, _) = setupdate_body();
+        let mut bodies = vec![];
+        for _ in 0..200 {
+            let pos = Point::new(rng.gen_range(0.0, 10.0), rng.gen_range(0.0, 10.0));
+            let body = Body::new(Uuid::new_v4(), pos, rng.gen_range(1.0, 10.0)).with_radius(0.01);
+            let body = Rc::new(RefCell::new(body));
+            qnode.insert(body.clone()).unwrap();
+            bodies.push(body);
+        }
+
+        for _ in 0..20 {
+            let p = Point::new(rng.gen_range(-5.0, 15.0), rng.gen_range(-5.0, 15.0));
+            let exact: f64 = bodies
+                .iter()
+                .map(|b| {
+                    let b = b.borrow();
+                    -2.0 * b.mass() / (b.position() - p).mag().max(b.radius())
+                })
+                .sum();
+            let approx = qnode.potential(p, 2.0);
+            assert!((approx - exact).abs() <= 0.01 * exact.abs());
+        }
+
+        // Finite on top of a body.
+        let on_top = bodies[0].borrow().position();
+        assert!(qnode.potential(on_top, 2.0).is_finite());
+    }
+
+    #[test]
+    fn softened() {
+        let cfg = Rc::new(QuadConfig {
+            capacity: 1,
+            theta: 0.5,
+            softening: 1.0,
+        });
+        let mut qnode = QuadNode::new(cfg, Rect::new(5.0, 5.0, 10.0, 10.0));
+        let body = Body::new(Uuid::new_v4(), Point::new(5.0, 5.0), 2.0);
+        qnode.insert(Rc::new(RefCell::new(body))).unwrap();
+
+        // One unit away, the body pulls as if it was √2 away, along the line between them.
+        let acc = qnode.acceleration(Point::new(6.0, 5.0), Uuid::nil(), 1.0);
+        assert!((acc.x + 2.0 / 2f64.powf(1.5)).abs() < 1e-12 && acc.y == 0.0);
+        let potential = qnode.potential(Point::new(6.0, 5.0), 1.0);
+        assert!((potential + 2.0 / 2f64.sqrt()).abs() < 1e-12);
+        // Right on top of it, the pull vanishes instead of blowing up.
+        assert_eq!(
+            qnode.acceleration(Point::new(5.0, 5.0), Uuid::nil(), 1.0),
+            Point::default()
+        );
+    }
+
+    #[test]
+    fn massless() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let (mut qnode, bodies) = setupdate_body();
+        for b in &bodies {
+            qnode.insert(b.clone()).unwrap();
+        }
+        let (mass, com) = (qnode.mass(), qnode.com());
+
+        // Massless bodies can be found, but don't add to the mass.
+        let id = Uuid::new_v4();
+        let tracer = Body::new(id, Point::new(2.5, 7.5), 0.0).with_radius(0.1);
+        qnode.insert(Rc::new(RefCell::new(tracer))).unwrap();
+        assert_eq!((qnode.mass(), qnode.com()), (mass, com));
+        assert_eq!(
+            qnode.pick(Point::new(2.5, 7.5), 0.0).unwrap().borrow().id(),
+            id
+        );
+        let (mut empty, _) = setupdate_body();
+        empty
+            .insert(qnode.pick(Point::new(2.5, 7.5), 0.0).unwrap())
+            .unwrap();
+        assert_eq!(
+            empty.acceleration(Point::new(1.0, 1.0), Uuid::nil(), 1.0),
+            Point::default()
+        );
+
+        // Evaluating many targets at once pulls just like one at a time.
+        let targets: Vec<Point> = (0..50)
+            .map(|_| Point::new(rng.gen_range(-5.0, 15.0), rng.gen_range(-5.0, 15.0)))
+            .collect();
+        for (target, acc) in targets.iter().zip(qnode.accelerations(&targets, 2.0)) {
+            assert_eq!(qnode.acceleration(*target, Uuid::nil(), 2.0), acc);
+        }
+    }
+
+    #[test]
+    fn same_position() {
+        let (mut qnode, _) = setupdate_body();
+        for _ in 0..3 {
+            let b = Body::new(Uuid::new_v4(), Point::new(2.0, 2.0), 1.0);
+            qnode.insert(Rc::new(RefCell::new(b))).unwrap();
+        }
+        assert_eq!(qnode.mass(), 3.0);
+        assert_eq!(qnode.iter().map(|v| v.bodies).sum::<usize>(), 3);
+    }
+
     #[test]
     fn rule_of_laws() {
         // Newton's first law states that:
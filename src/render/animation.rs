@@ -0,0 +1,353 @@
+use super::*;
+
+/// File format of an animation recorded by a [`Recorder`](./struct.Recorder.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AnimationFormat {
+    /// At most 256 colours per frame, see [`Quantization`](./enum.Quantization.html), but plays
+    /// everywhere.
+    Gif,
+    /// Animated PNG, in full colour.
+    Apng,
+}
+
+impl AnimationFormat {
+    /// Also the usual file extension, which the command line picks the format by.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Apng => "apng",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "image/gif",
+            AnimationFormat::Apng => "image/apng",
+        }
+    }
+}
+
+impl std::str::FromStr for AnimationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gif" => Ok(AnimationFormat::Gif),
+            "apng" | "png" => Ok(AnimationFormat::Apng),
+            _ => Err(format!("Unknown animation format: {}", s)),
+        }
+    }
+}
+
+/// How the colours of a GIF are reduced to its palette.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Quantization {
+    /// Pick the `colors` that fit the frames of the animation best, at most 256.
+    Adaptive { colors: usize },
+    /// Map every frame onto the same 216 web-safe colours. Faster, but with visible banding in
+    /// trails and glows.
+    WebSafe,
+}
+
+impl Quantization {
+    /// The palette to pass to `--palette`. `adaptive` leaves the number of colours to
+    /// `--colors`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quantization::Adaptive { .. } => "adaptive",
+            Quantization::WebSafe => "web-safe",
+        }
+    }
+}
+
+impl Default for Quantization {
+    fn default() -> Self {
+        Quantization::Adaptive { colors: 256 }
+    }
+}
+
+impl std::str::FromStr for Quantization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "adaptive" => Ok(Quantization::default()),
+            "web-safe" => Ok(Quantization::WebSafe),
+            _ => Err(format!("Unknown palette: {}", s)),
+        }
+    }
+}
+
+/// How an animation is recorded.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AnimationConfig {
+    pub format: AnimationFormat,
+    /// Size of the animation in pixels. Frames drawn at a different size are scaled to fit.
+    pub width: usize,
+    pub height: usize,
+    /// Draw a frame on every `every`-th tick, skipping the ones in between.
+    pub every: usize,
+    /// Frames per second the animation plays at.
+    pub fps: f64,
+    /// Only used for GIFs.
+    pub palette: Quantization,
+}
+
+impl AnimationConfig {
+    /// Record every tick as a frame of `width` by `height` pixels, played at 30 frames per
+    /// second.
+    pub fn new(format: AnimationFormat, width: usize, height: usize) -> Self {
+        Self {
+            format,
+            width,
+            height,
+            every: 1,
+            fps: 30.0,
+            palette: Quantization::default(),
+        }
+    }
+}
+
+/// Frames an adaptive GIF palette is picked from, spread evenly over the animation.
+const PALETTE_FRAMES: usize = 16;
+
+/// Index of the colour closest to an RGBA pixel in a GIF palette.
+type PaletteIndex = Box<dyn Fn(&[u8]) -> u8>;
+
+/// Records ticks of a [`Universe`](../universe/struct.Universe.html) into an animated GIF or PNG,
+/// drawn with a [`Raster`](./struct.Raster.html). Frames are only kept while recording and
+/// encoded when the animation is finished, so capturing a tick stays cheap.
+///
+/// ```
+/// use liniverse::{scenario, AnimationConfig, AnimationFormat, Recorder};
+///
+/// let mut universe = scenario::solar_system((160.0, 120.0), false);
+/// let mut recorder = Recorder::new(AnimationConfig::new(AnimationFormat::Gif, 160, 120)).unwrap();
+/// for _ in 0..10 {
+///     universe.tick();
+///     recorder.capture(&universe).unwrap();
+/// }
+/// let gif = recorder.finish().unwrap();
+/// assert!(gif.starts_with(b"GIF89a"));
+/// ```
+pub struct Recorder {
+    cfg: AnimationConfig,
+    /// RGBA pixels of the frames recorded so far, at the size of the animation.
+    frames: Vec<Vec<u8>>,
+    /// Reused for drawing each frame at the size of the universe's viewport.
+    raster: Option<Raster>,
+    ticks: usize,
+}
+
+impl Recorder {
+    pub fn new(cfg: AnimationConfig) -> Result<Self, String> {
+        if cfg.width == 0 || cfg.height == 0 {
+            return Err("Animations need to be at least 1 by 1 pixels".to_string());
+        }
+        if cfg.every == 0 || cfg.fps.is_nan() || cfg.fps <= 0.0 {
+            return Err("Animations need to record and play at least one frame".to_string());
+        }
+
+        if cfg.format == AnimationFormat::Gif
+            && (cfg.width > u16::MAX as usize || cfg.height > u16::MAX as usize)
+        {
+            return Err("GIFs can be at most 65535 pixels wide and high".to_string());
+        }
+
+        Ok(Self {
+            cfg,
+            frames: vec![],
+            raster: None,
+            ticks: 0,
+        })
+    }
+
+    pub fn config(&self) -> AnimationConfig {
+        self.cfg
+    }
+
+    /// Number of frames recorded so far.
+    pub fn frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Called once per tick, draws `universe` as the next frame on every
+    /// [`every`](./struct.AnimationConfig.html#structfield.every)-th call, starting with the
+    /// first.
+    pub fn capture(&mut self, universe: &Universe) -> Result<(), String> {
+        let tick = self.ticks;
+        self.ticks += 1;
+        if tick % self.cfg.every != 0 {
+            return Ok(());
+        }
+
+        let (width, height) = universe.camera().viewport();
+        let (width, height) = (width.round() as usize, height.round() as usize);
+        let mut raster = match self.raster.take() {
+            Some(raster) if raster.width() == width && raster.height() == height => raster,
+            _ => Raster::new(width, height),
+        };
+        universe.render(&mut raster);
+        let result = self.push(&raster);
+        self.raster = Some(raster);
+        result
+    }
+
+    /// Add `raster` as the next frame, scaled to the size of the animation.
+    pub fn push(&mut self, raster: &Raster) -> Result<(), String> {
+        let (width, height) = (self.cfg.width, self.cfg.height);
+        let scaled;
+        let raster = if raster.width() == width && raster.height() == height {
+            raster
+        } else {
+            scaled = raster.scaled(width, height);
+            &scaled
+        };
+        self.frames.push(raster.pixels().to_vec());
+        Ok(())
+    }
+
+    /// Finish the animation and hand out its bytes.
+    pub fn finish(self) -> Result<Vec<u8>, String> {
+        if self.frames.is_empty() {
+            return Err("No frames were recorded".to_string());
+        }
+
+        match self.cfg.format {
+            AnimationFormat::Gif => self.encode_gif().map_err(|e| e.to_string()),
+            AnimationFormat::Apng => {
+                let error = |e: png::EncodingError| e.to_string();
+                let mut bytes = vec![];
+                let (width, height) = (self.cfg.width as u32, self.cfg.height as u32);
+                let mut encoder = png::Encoder::new(&mut bytes, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder
+                    .set_animated(self.frames.len() as u32, 0)
+                    .map_err(error)?;
+                let delay = (1000.0 / self.cfg.fps).round().clamp(1.0, u16::MAX as f64) as u16;
+                encoder.set_frame_delay(delay, 1000).map_err(error)?;
+
+                let mut writer = encoder.write_header().map_err(error)?;
+                for frame in &self.frames {
+                    writer.write_image_data(frame).map_err(error)?;
+                }
+                writer.finish().map_err(error)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Reduce the colours of all frames to one palette and encode them as a GIF. An adaptive
+    /// palette is picked from a few frames spread over the animation rather than from every
+    /// single one.
+    fn encode_gif(&self) -> Result<Vec<u8>, gif::EncodingError> {
+        let (width, height) = (self.cfg.width as u16, self.cfg.height as u16);
+        let (palette, index): (Vec<u8>, PaletteIndex) = match self.cfg.palette {
+            Quantization::Adaptive { colors } => {
+                let step = self.frames.len().div_ceil(PALETTE_FRAMES);
+                let sample: Vec<u8> = self
+                    .frames
+                    .iter()
+                    .step_by(step)
+                    .flatten()
+                    .copied()
+                    .collect();
+                let quant = color_quant::NeuQuant::new(10, colors.clamp(2, 256), &sample);
+                (
+                    quant.color_map_rgb(),
+                    Box::new(move |px| quant.index_of(px) as u8),
+                )
+            }
+            Quantization::WebSafe => {
+                let level = |c: u8| (c as usize + 25) / 51;
+                let index =
+                    move |px: &[u8]| (level(px[0]) * 36 + level(px[1]) * 6 + level(px[2])) as u8;
+                (web_safe_palette(), Box::new(index))
+            }
+        };
+
+        let mut encoder = gif::Encoder::new(vec![], width, height, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        let delay = (100.0 / self.cfg.fps).round().clamp(1.0, u16::MAX as f64) as u16;
+        for pixels in &self.frames {
+            let indices: Vec<u8> = pixels.chunks_exact(4).map(&index).collect();
+            let mut frame = gif::Frame::from_indexed_pixels(width, height, indices, None);
+            frame.delay = delay;
+            encoder.write_frame(&frame)?;
+        }
+        encoder.into_inner().map_err(Into::into)
+    }
+}
+
+/// The 216 colours whose channels are all multiples of 51, as RGB bytes.
+fn web_safe_palette() -> Vec<u8> {
+    let mut palette = Vec::with_capacity(216 * 3);
+    for r in 0..6 {
+        for g in 0..6 {
+            for b in 0..6 {
+                palette.extend_from_slice(&[r * 51, g * 51, b * 51]);
+            }
+        }
+    }
+    palette
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(cfg: AnimationConfig) -> (Vec<u8>, usize) {
+        let mut universe = Universe::new((80.0, 60.0));
+        universe.add(Planet::new_sun(40.0, 30.0));
+        universe.add(Planet::new(60.0, 30.0, 1.0, 3.0, Point::new(0.0, 0.5)));
+        let mut recorder = Recorder::new(cfg).unwrap();
+        for _ in 0..10 {
+            recorder.capture(&universe).unwrap();
+            universe.tick();
+        }
+        let frames = recorder.frames();
+        (recorder.finish().unwrap(), frames)
+    }
+
+    #[test]
+    fn gif() {
+        let mut cfg = AnimationConfig::new(AnimationFormat::Gif, 40, 30);
+        cfg.every = 3;
+        let (bytes, frames) = record(cfg);
+        assert_eq!(frames, 4);
+        assert!(bytes.starts_with(b"GIF89a"));
+
+        let mut decoder = gif::DecodeOptions::new().read_info(&bytes[..]).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (40, 30));
+        let mut decoded = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 3);
+            decoded += 1;
+        }
+        assert_eq!(decoded, 4);
+
+        cfg.palette = Quantization::WebSafe;
+        let (bytes, _) = record(cfg);
+        assert!(bytes.starts_with(b"GIF89a"));
+    }
+
+    #[test]
+    fn apng() {
+        let mut cfg = AnimationConfig::new(AnimationFormat::Apng, 80, 60);
+        cfg.every = 2;
+        let (bytes, frames) = record(cfg);
+        assert_eq!(frames, 5);
+
+        let decoder = png::Decoder::new(&bytes[..]);
+        let reader = decoder.read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!(control.num_frames, 5);
+        assert_eq!(control.num_plays, 0);
+
+        let recorder = Recorder::new(cfg).unwrap();
+        assert!(recorder.finish().is_err());
+        cfg.every = 0;
+        assert!(Recorder::new(cfg).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Backends the [`Universe`](../universe/struct.Universe.html) is drawn with.
+//!
+//! The scene is described once, in
+//! [`Universe::render`](../universe/struct.Universe.html#method.render), against the
+//! [`Renderer`](./trait.Renderer.html) trait. It's drawn onto the page's canvas by a
+//! [`CanvasRenderer`](./struct.CanvasRenderer.html), into an image in memory by a
+//! [`Raster`](./struct.Raster.html), e.g. to write frames of a headless run, and into an SVG
+//! document by [`Svg`](./struct.Svg.html) for figures and as text by
+//! [`Terminal`](./struct.Terminal.html) for quick looks without a screen. A
+//! [`Recorder`](./struct.Recorder.html) turns rasterized ticks into animated GIFs or PNGs.
+use std::f64::consts::PI;
+
+use super::*;
+
+mod animation;
+pub use animation::{AnimationConfig, AnimationFormat, Quantization, Recorder};
+mod font;
+mod raster;
+pub use raster::Raster;
+mod svg;
+pub use svg::Svg;
+mod terminal;
+pub use terminal::{Glyphs, Terminal};
+
+/// Something the scene can be drawn onto. All coordinates and sizes are in pixels.
+///
+/// Shapes are drawn with the opacity last set by [`set_alpha`](#tymethod.set_alpha), on top of
+/// what's already there.
+pub trait Renderer {
+    /// Wipe everything drawn so far.
+    fn clear(&mut self);
+    /// Opacity of everything drawn from now on, from 0 to 1.
+    fn set_alpha(&mut self, alpha: f64);
+    fn fill_circle(&mut self, center: Point, radius: f64, color: Color);
+    fn stroke_circle(&mut self, center: Point, radius: f64, width: f64, color: Color);
+    /// Draw a line through all `points`, in order.
+    fn stroke_line(&mut self, points: &[Point], width: f64, color: Color);
+    fn fill_polygon(&mut self, points: &[Point], color: Color);
+    /// Draw the outline of a polygon, going round from the last point back to the first.
+    fn stroke_polygon(&mut self, points: &[Point], width: f64, color: Color) {
+        let mut outline = points.to_vec();
+        outline.extend(points.first());
+        self.stroke_line(&outline, width, color);
+    }
+    /// Write `text` starting at `pos`, which is on the baseline of the text.
+    fn fill_text(&mut self, text: &str, pos: Point, color: Color);
+
+    fn fill_rect(&mut self, pos: Point, width: f64, height: f64, color: Color) {
+        let corners = [
+            pos,
+            Point::new(pos.x + width, pos.y),
+            Point::new(pos.x + width, pos.y + height),
+            Point::new(pos.x, pos.y + height),
+        ];
+        self.fill_polygon(&corners, color);
+    }
+}
+
+/// Draws onto a canvas of the page.
+pub struct CanvasRenderer<'a> {
+    ctx: &'a web_sys::CanvasRenderingContext2d,
+    size: (f64, f64),
+}
+
+impl<'a> CanvasRenderer<'a> {
+    /// Draw onto `ctx`, covering `size` pixels.
+    pub fn new(ctx: &'a web_sys::CanvasRenderingContext2d, size: (f64, f64)) -> Self {
+        Self { ctx, size }
+    }
+
+    fn trace(&self, points: &[Point]) {
+        self.ctx.begin_path();
+        for (i, p) in points.iter().enumerate() {
+            if i == 0 {
+                self.ctx.move_to(p.x, p.y);
+            } else {
+                self.ctx.line_to(p.x, p.y);
+            }
+        }
+    }
+
+    fn arc(&self, center: Point, radius: f64) {
+        self.ctx.begin_path();
+        self.ctx
+            .arc(center.x, center.y, radius, 0.0, PI * 2.0)
+            .unwrap();
+    }
+}
+
+impl<'a> Renderer for CanvasRenderer<'a> {
+    fn clear(&mut self) {
+        self.ctx.clear_rect(0.0, 0.0, self.size.0, self.size.1);
+    }
+
+    fn set_alpha(&mut self, alpha: f64) {
+        self.ctx.set_global_alpha(alpha);
+    }
+
+    fn fill_circle(&mut self, center: Point, radius: f64, color: Color) {
+        self.arc(center, radius);
+        self.ctx.set_fill_style_str(&color.to_css());
+        self.ctx.fill();
+    }
+
+    fn stroke_circle(&mut self, center: Point, radius: f64, width: f64, color: Color) {
+        self.arc(center, radius);
+        self.ctx.set_stroke_style_str(&color.to_css());
+        self.ctx.set_line_width(width);
+        self.ctx.stroke();
+    }
+
+    fn stroke_line(&mut self, points: &[Point], width: f64, color: Color) {
+        self.trace(points);
+        self.ctx.set_stroke_style_str(&color.to_css());
+        self.ctx.set_line_width(width);
+        self.ctx.stroke();
+    }
+
+    fn fill_polygon(&mut self, points: &[Point], color: Color) {
+        self.trace(points);
+        self.ctx.close_path();
+        self.ctx.set_fill_style_str(&color.to_css());
+        self.ctx.fill();
+    }
+
+    fn stroke_polygon(&mut self, points: &[Point], width: f64, color: Color) {
+        self.trace(points);
+        self.ctx.close_path();
+        self.ctx.set_stroke_style_str(&color.to_css());
+        self.ctx.set_line_width(width);
+        self.ctx.stroke();
+    }
+
+    fn fill_text(&mut self, text: &str, pos: Point, color: Color) {
+        self.ctx.set_fill_style_str(&color.to_css());
+        self.ctx.set_font("12px 'Open Sans', sans-serif");
+        self.ctx.fill_text(text, pos.x, pos.y).unwrap();
+    }
+
+    fn fill_rect(&mut self, pos: Point, width: f64, height: f64, color: Color) {
+        self.ctx.set_fill_style_str(&color.to_css());
+        self.ctx.fill_rect(pos.x, pos.y, width, height);
+    }
+}
+
+/// Whether `p` lies inside the polygon, by the even-odd rule.
+fn contains(points: &[Point], p: Point) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
@@ -0,0 +1,379 @@
+use std::io::Write;
+
+use super::*;
+
+/// Font pixels per pixel of the image, for text written by [`Raster`](./struct.Raster.html).
+const FONT_SCALE: usize = 2;
+/// Samples per pixel along each axis when filling polygons.
+const SUBSAMPLES: usize = 4;
+
+/// An RGBA image in memory, drawn with anti-aliasing by a software rasterizer.
+///
+/// Each shape is first rendered into a coverage mask, which is then blended onto the image. That
+/// way, the segments of a translucent line don't double up where they meet.
+pub struct Raster {
+    width: usize,
+    height: usize,
+    /// Four bytes per pixel, row by row from the top left.
+    pixels: Vec<u8>,
+    background: [u8; 4],
+    alpha: f64,
+    /// How much of each pixel the shape being drawn covers, from 0 to 1.
+    coverage: Vec<f32>,
+    /// Pixels with any coverage, so the mask can be blended and reset without going over all of
+    /// them.
+    touched: Vec<usize>,
+}
+
+impl Raster {
+    /// Create an image of `width` by `height` pixels, cleared to opaque black.
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut raster = Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+            background: [0, 0, 0, 255],
+            alpha: 1.0,
+            coverage: vec![0.0; width * height],
+            touched: vec![],
+        };
+        raster.clear();
+        raster
+    }
+
+    /// Clear to `color` instead of black from now on, with the given opacity.
+    pub fn with_background(mut self, color: Color, alpha: f64) -> Self {
+        let alpha = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+        self.background = [color.r, color.g, color.b, alpha];
+        self.clear();
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The RGBA bytes of all pixels, row by row from the top left.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The RGBA values of the pixel at `x`, `y`.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * self.width + x) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Encode the image as PNG.
+    pub fn write_png(&self, w: impl Write) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()
+    }
+
+    /// The image encoded as PNG.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        // Writing into memory only fails on a broken encoder.
+        self.write_png(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// A copy of the image scaled to `width` by `height` pixels. Each pixel is the average of the
+    /// pixels it covers in the original.
+    pub fn scaled(&self, width: usize, height: usize) -> Raster {
+        let mut scaled = Raster::new(width, height);
+        scaled.background = self.background;
+        // The range of original pixels covering pixel `i` of `n` along an axis of `size`.
+        let span = |i: usize, n: usize, size: usize| {
+            let from = i * size / n;
+            let to = ((i + 1) * size / n).max(from + 1).min(size);
+            from..to
+        };
+
+        for y in 0..height {
+            let rows = span(y, height, self.height);
+            for x in 0..width {
+                let cols = span(x, width, self.width);
+                let mut sum = [0usize; 4];
+                let mut count = 0;
+                for sy in rows.clone() {
+                    for sx in cols.clone() {
+                        let i = (sy * self.width + sx) * 4;
+                        for (s, c) in sum.iter_mut().zip(&self.pixels[i..i + 4]) {
+                            *s += *c as usize;
+                        }
+                        count += 1;
+                    }
+                }
+                let i = (y * width + x) * 4;
+                for (c, s) in scaled.pixels[i..i + 4].iter_mut().zip(&sum) {
+                    *c = ((s + count / 2) / count) as u8;
+                }
+            }
+        }
+        scaled
+    }
+
+    /// Cover the pixel at `x`, `y` by at least `c`.
+    fn cover(&mut self, x: i64, y: i64, c: f64) {
+        if c <= 0.0 || x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = y as usize * self.width + x as usize;
+        if self.coverage[i] == 0.0 {
+            self.touched.push(i);
+        }
+        self.coverage[i] = self.coverage[i].max(c.min(1.0) as f32);
+    }
+
+    /// Blend `color` onto all covered pixels and reset the coverage mask.
+    fn flush(&mut self, color: Color) {
+        let src = [color.r as f64, color.g as f64, color.b as f64];
+        for &i in &self.touched {
+            let a = self.alpha * self.coverage[i] as f64;
+            self.coverage[i] = 0.0;
+            let px = &mut self.pixels[i * 4..i * 4 + 4];
+            let dst_a = px[3] as f64 / 255.0;
+            let out_a = a + dst_a * (1.0 - a);
+            if out_a <= 0.0 {
+                continue;
+            }
+            for (channel, src) in px.iter_mut().zip(&src) {
+                let blended = (src * a + *channel as f64 * dst_a * (1.0 - a)) / out_a;
+                *channel = blended.round().clamp(0.0, 255.0) as u8;
+            }
+            px[3] = (out_a * 255.0).round() as u8;
+        }
+        self.touched.clear();
+    }
+
+    /// Pixel bounds of the box from `min` to `max`, clamped to the image, or `None` if it lies
+    /// outside of it.
+    fn bounds(&self, min: Point, max: Point) -> Option<(i64, i64, i64, i64)> {
+        if !(min.x.is_finite() && min.y.is_finite() && max.x.is_finite() && max.y.is_finite()) {
+            return None;
+        }
+        let x0 = (min.x.floor() as i64).max(0);
+        let y0 = (min.y.floor() as i64).max(0);
+        let x1 = (max.x.ceil() as i64).min(self.width as i64 - 1);
+        let y1 = (max.y.ceil() as i64).min(self.height as i64 - 1);
+        if x0 > x1 || y0 > y1 {
+            None
+        } else {
+            Some((x0, y0, x1, y1))
+        }
+    }
+
+    /// Cover pixels by how far their centers are from a circle of `radius` around `center`, with
+    /// `coverage` mapping that distance to a coverage.
+    fn cover_circle(&mut self, center: Point, reach: f64, coverage: impl Fn(f64) -> f64) {
+        let margin = Point::new(reach + 1.0, reach + 1.0);
+        if let Some((x0, y0, x1, y1)) = self.bounds(center - margin, center + margin) {
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let pixel = Point::new(x as f64 + 0.5, y as f64 + 0.5);
+                    self.cover(x, y, coverage(pixel.distance_to(center)));
+                }
+            }
+        }
+    }
+
+    /// Cover the pixels along the segment from `a` to `b`, `width` pixels wide. Thin lines are
+    /// covered by their width, which is how much of a pixel they'd take up.
+    fn cover_segment(&mut self, a: Point, b: Point, width: f64) {
+        let half = width / 2.0;
+        let pad = half + 1.0;
+        let min = Point::new(a.x.min(b.x) - pad, a.y.min(b.y) - pad);
+        let max = Point::new(a.x.max(b.x) + pad, a.y.max(b.y) + pad);
+        let (_, y0, _, y1) = match self.bounds(min, max) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let d = b - a;
+        let length2 = d.x * d.x + d.y * d.y;
+
+        for y in y0..=y1 {
+            // Only look at the part of the row the segment passes through.
+            let (from, to) = if d.y.abs() < 1e-9 {
+                (a.x.min(b.x), a.x.max(b.x))
+            } else {
+                let t0 = ((y as f64 - pad - a.y) / d.y).clamp(0.0, 1.0);
+                let t1 = ((y as f64 + 1.0 + pad - a.y) / d.y).clamp(0.0, 1.0);
+                let (x0, x1) = (a.x + d.x * t0, a.x + d.x * t1);
+                (x0.min(x1), x0.max(x1))
+            };
+            let x0 = ((from - pad).floor() as i64).max(0);
+            let x1 = ((to + pad).ceil() as i64).min(self.width as i64 - 1);
+            for x in x0..=x1 {
+                let p = Point::new(x as f64 + 0.5, y as f64 + 0.5);
+                let t = if length2 == 0.0 {
+                    0.0
+                } else {
+                    (((p.x - a.x) * d.x + (p.y - a.y) * d.y) / length2).clamp(0.0, 1.0)
+                };
+                let distance = p.distance_to(a + d * t);
+                self.cover(x, y, (half + 0.5 - distance).clamp(0.0, width.min(1.0)));
+            }
+        }
+    }
+}
+
+impl Renderer for Raster {
+    fn clear(&mut self) {
+        for px in self.pixels.chunks_exact_mut(4) {
+            px.copy_from_slice(&self.background);
+        }
+    }
+
+    fn set_alpha(&mut self, alpha: f64) {
+        self.alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    fn fill_circle(&mut self, center: Point, radius: f64, color: Color) {
+        self.cover_circle(center, radius, |d| (radius + 0.5 - d).clamp(0.0, 1.0));
+        self.flush(color);
+    }
+
+    fn stroke_circle(&mut self, center: Point, radius: f64, width: f64, color: Color) {
+        let half = width / 2.0;
+        self.cover_circle(center, radius + half, |d| {
+            (half + 0.5 - (d - radius).abs()).clamp(0.0, width.min(1.0))
+        });
+        self.flush(color);
+    }
+
+    fn stroke_line(&mut self, points: &[Point], width: f64, color: Color) {
+        for pair in points.windows(2) {
+            self.cover_segment(pair[0], pair[1], width);
+        }
+        self.flush(color);
+    }
+
+    fn fill_polygon(&mut self, points: &[Point], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+        let min = points
+            .iter()
+            .fold(points[0], |m, p| Point::new(m.x.min(p.x), m.y.min(p.y)));
+        let max = points
+            .iter()
+            .fold(points[0], |m, p| Point::new(m.x.max(p.x), m.y.max(p.y)));
+        let (x0, y0, x1, y1) = match self.bounds(min, max) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let step = 1.0 / SUBSAMPLES as f64;
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let mut inside = 0;
+                for sy in 0..SUBSAMPLES {
+                    for sx in 0..SUBSAMPLES {
+                        let p = Point::new(
+                            x as f64 + (sx as f64 + 0.5) * step,
+                            y as f64 + (sy as f64 + 0.5) * step,
+                        );
+                        if contains(points, p) {
+                            inside += 1;
+                        }
+                    }
+                }
+                self.cover(x, y, inside as f64 / (SUBSAMPLES * SUBSAMPLES) as f64);
+            }
+        }
+        self.flush(color);
+    }
+
+    fn fill_text(&mut self, text: &str, pos: Point, color: Color) {
+        let top = pos.y.round() as i64 - (font::HEIGHT * FONT_SCALE) as i64;
+        let mut left = pos.x.round() as i64;
+        for c in text.chars() {
+            for (row, bits) in font::glyph(c).iter().enumerate() {
+                for col in 0..font::WIDTH {
+                    if bits & (1 << (font::WIDTH - 1 - col)) == 0 {
+                        continue;
+                    }
+                    for dy in 0..FONT_SCALE {
+                        for dx in 0..FONT_SCALE {
+                            let x = left + (col * FONT_SCALE + dx) as i64;
+                            let y = top + (row * FONT_SCALE + dy) as i64;
+                            self.cover(x, y, 1.0);
+                        }
+                    }
+                }
+            }
+            left += ((font::WIDTH + 1) * FONT_SCALE) as i64;
+        }
+        self.flush(color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shapes() {
+        let red = Color::new(255, 0, 0);
+        let mut raster = Raster::new(40, 30);
+        assert_eq!(raster.pixel(0, 0), [0, 0, 0, 255]);
+
+        raster.fill_circle(Point::new(10.5, 10.5), 4.7, red);
+        assert_eq!(raster.pixel(10, 10), [255, 0, 0, 255]);
+        assert_eq!(raster.pixel(20, 10), [0, 0, 0, 255]);
+        // The edge is smoothed.
+        let edge = raster.pixel(15, 10)[0];
+        assert!(edge > 0 && edge < 255);
+
+        // Segments of translucent lines don't double up where they meet.
+        raster.set_alpha(0.5);
+        let line = [
+            Point::new(20.0, 20.5),
+            Point::new(30.0, 20.5),
+            Point::new(39.0, 20.5),
+        ];
+        raster.stroke_line(&line, 1.0, Color::WHITE);
+        assert_eq!(raster.pixel(25, 20), raster.pixel(30, 20));
+        assert_eq!(raster.pixel(30, 20)[0], 128);
+
+        raster.set_alpha(1.0);
+        raster.fill_rect(Point::new(0.0, 25.0), 4.0, 5.0, Color::WHITE);
+        assert_eq!(raster.pixel(2, 27), [255, 255, 255, 255]);
+        assert_eq!(raster.pixel(4, 27), [0, 0, 0, 255]);
+
+        let half = raster.scaled(20, 15);
+        assert_eq!(half.pixel(5, 5), [255, 0, 0, 255]);
+        assert_eq!(half.pixel(1, 13), [255, 255, 255, 255]);
+        assert_eq!(raster.scaled(80, 60).pixel(20, 20), [255, 0, 0, 255]);
+
+        raster.clear();
+        assert_eq!(raster.pixel(10, 10), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn png() {
+        let raster = Raster::new(8, 4).with_background(Color::SUN, 1.0);
+        let bytes = raster.to_png();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+
+        let decoder = png::Decoder::new(&bytes[..]);
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, raster.pixels());
+    }
+}
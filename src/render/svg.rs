@@ -0,0 +1,296 @@
+use std::fmt::Write;
+
+use super::*;
+
+/// An SVG document of the scene, e.g. for figures in reports.
+///
+/// The same scene always gives the same document, down to the byte: coordinates are written with
+/// two decimals and elements in the order they were drawn in. That makes documents easy to
+/// compare in tests.
+///
+/// ```
+/// use liniverse::{Color, Point, Renderer, Svg};
+///
+/// let mut svg = Svg::new(100.0, 50.0);
+/// svg.fill_circle(Point::new(50.0, 25.0), 10.0, Color::SUN);
+/// assert!(svg.to_string().contains(r##"<circle cx="50" cy="25" r="10" fill="#ffc430"/>"##));
+/// ```
+pub struct Svg {
+    width: f64,
+    height: f64,
+    background: Option<(Color, f64)>,
+    alpha: f64,
+    /// All elements drawn since the last [`clear`](#method.clear).
+    elements: String,
+}
+
+impl Svg {
+    /// Create a document of `width` by `height` pixels on an opaque black background.
+    pub fn new(width: f64, height: f64) -> Self {
+        let mut svg = Self {
+            width,
+            height,
+            background: Some((Color::BLACK, 1.0)),
+            alpha: 1.0,
+            elements: String::new(),
+        };
+        svg.clear();
+        svg
+    }
+
+    /// Use `color` as background instead of black, with the given opacity. Fully transparent
+    /// backgrounds are left out.
+    pub fn with_background(mut self, color: Color, alpha: f64) -> Self {
+        self.background = if alpha > 0.0 {
+            Some((color, alpha.min(1.0)))
+        } else {
+            None
+        };
+        self.clear();
+        self
+    }
+
+    /// Write an element with the current opacity, unless it's invisible. `attributes` start with
+    /// a space.
+    fn element(&mut self, name: &str, attributes: &str, content: Option<&str>) {
+        if self.alpha <= 0.0 {
+            return;
+        }
+        let opacity = opacity(self.alpha);
+        match content {
+            Some(content) => writeln!(
+                self.elements,
+                "<{}{}{}>{}</{}>",
+                name, attributes, opacity, content, name
+            ),
+            None => writeln!(self.elements, "<{}{}{}/>", name, attributes, opacity),
+        }
+        .unwrap();
+    }
+}
+
+impl std::fmt::Display for Svg {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (width, height) = (number(self.width), number(self.height));
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            width, height, width, height
+        )?;
+        write!(f, "{}", self.elements)?;
+        writeln!(f, "</svg>")
+    }
+}
+
+impl Renderer for Svg {
+    fn clear(&mut self) {
+        self.elements.clear();
+        if let Some((color, alpha)) = self.background {
+            writeln!(
+                self.elements,
+                r#"<rect width="100%" height="100%" fill="{}"{}/>"#,
+                color.to_css(),
+                opacity(alpha)
+            )
+            .unwrap();
+        }
+    }
+
+    fn set_alpha(&mut self, alpha: f64) {
+        self.alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    fn fill_circle(&mut self, center: Point, radius: f64, color: Color) {
+        let attributes = format!(
+            r#" cx="{}" cy="{}" r="{}" fill="{}""#,
+            number(center.x),
+            number(center.y),
+            number(radius),
+            color.to_css()
+        );
+        self.element("circle", &attributes, None);
+    }
+
+    fn stroke_circle(&mut self, center: Point, radius: f64, width: f64, color: Color) {
+        let attributes = format!(
+            r#" cx="{}" cy="{}" r="{}" fill="none"{}"#,
+            number(center.x),
+            number(center.y),
+            number(radius),
+            stroke(width, color)
+        );
+        self.element("circle", &attributes, None);
+    }
+
+    fn stroke_line(&mut self, points: &[Point], width: f64, color: Color) {
+        if points.is_empty() {
+            return;
+        }
+        let d = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let command = if i == 0 { "M" } else { "L" };
+                format!("{}{} {}", command, number(p.x), number(p.y))
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let attributes = format!(
+            r#" d="{}" fill="none"{} stroke-linejoin="round""#,
+            d,
+            stroke(width, color)
+        );
+        self.element("path", &attributes, None);
+    }
+
+    fn fill_polygon(&mut self, points: &[Point], color: Color) {
+        let attributes = format!(r#" points="{}" fill="{}""#, coords(points), color.to_css());
+        self.element("polygon", &attributes, None);
+    }
+
+    /// Axis-aligned rectangles, like the nodes of the quad tree while the view isn't rotated,
+    /// are written as rects.
+    fn stroke_polygon(&mut self, points: &[Point], width: f64, color: Color) {
+        let (name, attributes) = match rect(points) {
+            Some((x, y, w, h)) => (
+                "rect",
+                format!(
+                    r#" x="{}" y="{}" width="{}" height="{}" fill="none"{}"#,
+                    number(x),
+                    number(y),
+                    number(w),
+                    number(h),
+                    stroke(width, color)
+                ),
+            ),
+            None => (
+                "polygon",
+                format!(
+                    r#" points="{}" fill="none"{}"#,
+                    coords(points),
+                    stroke(width, color)
+                ),
+            ),
+        };
+        self.element(name, &attributes, None);
+    }
+
+    fn fill_text(&mut self, text: &str, pos: Point, color: Color) {
+        let attributes = format!(
+            r#" x="{}" y="{}" fill="{}" font-family="Open Sans, sans-serif" font-size="12""#,
+            number(pos.x),
+            number(pos.y),
+            color.to_css()
+        );
+        self.element("text", &attributes, Some(&escape(text)));
+    }
+
+    fn fill_rect(&mut self, pos: Point, width: f64, height: f64, color: Color) {
+        let attributes = format!(
+            r#" x="{}" y="{}" width="{}" height="{}" fill="{}""#,
+            number(pos.x),
+            number(pos.y),
+            number(width),
+            number(height),
+            color.to_css()
+        );
+        self.element("rect", &attributes, None);
+    }
+}
+
+/// Format a number with at most two decimals and without trailing zeros.
+fn number(x: f64) -> String {
+    let s = format!("{:.2}", x);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" | "" => "0".to_string(),
+        s => s.to_string(),
+    }
+}
+
+fn coords(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", number(p.x), number(p.y)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn stroke(width: f64, color: Color) -> String {
+    format!(
+        r#" stroke="{}" stroke-width="{}""#,
+        color.to_css(),
+        number(width)
+    )
+}
+
+/// The opacity attribute for `alpha`, left out when fully opaque.
+fn opacity(alpha: f64) -> String {
+    if alpha < 1.0 {
+        format!(r#" opacity="{}""#, number(alpha))
+    } else {
+        String::new()
+    }
+}
+
+/// Position and size of the polygon if it's a rectangle with edges along the axes.
+fn rect(points: &[Point]) -> Option<(f64, f64, f64, f64)> {
+    if points.len() != 4 {
+        return None;
+    }
+    let axis_aligned = (0..4).all(|i| {
+        let (a, b) = (points[i], points[(i + 1) % 4]);
+        // Compared as written, so rounding errors of the camera don't get in the way.
+        number(a.x) == number(b.x) || number(a.y) == number(b.y)
+    });
+    if !axis_aligned {
+        return None;
+    }
+    let (x0, x1) = (points[0].x.min(points[2].x), points[0].x.max(points[2].x));
+    let (y0, y1) = (points[0].y.min(points[2].y), points[0].y.max(points[2].y));
+    Some((x0, y0, x1 - x0, y1 - y0))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn document() {
+        let mut svg = Svg::new(100.0, 50.0).with_background(Color::WHITE, 0.0);
+        svg.fill_circle(Point::new(10.0, 20.0), 5.5, Color::SUN);
+        svg.set_alpha(0.4);
+        let rect = [
+            Point::new(0.0, 0.0),
+            Point::new(50.0, 0.0),
+            Point::new(50.0, 25.0),
+            Point::new(0.0, 25.0),
+        ];
+        svg.stroke_polygon(&rect, 0.5, Color::CYAN);
+        svg.set_alpha(1.0);
+        svg.stroke_line(
+            &[Point::new(1.0 / 3.0, -0.001), Point::new(2.0, 3.0)],
+            1.0,
+            Color::WHITE,
+        );
+        svg.fill_text("a < b", Point::new(10.0, 40.0), Color::WHITE);
+
+        let expected = r##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50" viewBox="0 0 100 50">
+<circle cx="10" cy="20" r="5.5" fill="#ffc430"/>
+<rect x="0" y="0" width="50" height="25" fill="none" stroke="#00ffff" stroke-width="0.5" opacity="0.4"/>
+<path d="M0.33 0 L2 3" fill="none" stroke="#ffffff" stroke-width="1" stroke-linejoin="round"/>
+<text x="10" y="40" fill="#ffffff" font-family="Open Sans, sans-serif" font-size="12">a &lt; b</text>
+</svg>
+"##;
+        assert_eq!(svg.to_string(), expected);
+
+        svg.clear();
+        assert!(!svg.to_string().contains("circle"));
+    }
+}
@@ -0,0 +1,438 @@
+use super::*;
+
+/// Characters a [`Terminal`](./struct.Terminal.html) draws with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Glyphs {
+    /// Braille patterns, with 2 by 4 dots per character.
+    Braille,
+    /// Half blocks, with 1 by 2 dots per character. For fonts without braille.
+    Blocks,
+}
+
+impl Glyphs {
+    /// What to pass to `--glyphs` to draw with these.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Glyphs::Braille => "braille",
+            Glyphs::Blocks => "blocks",
+        }
+    }
+
+    /// Number of dots per character, across and down.
+    pub fn dots(&self) -> (usize, usize) {
+        match self {
+            Glyphs::Braille => (2, 4),
+            Glyphs::Blocks => (1, 2),
+        }
+    }
+
+    /// The character showing the dots set in `dots`, numbered row by row from the top left.
+    fn glyph(&self, dots: u8) -> char {
+        match self {
+            Glyphs::Braille => {
+                // Braille numbers its dots column by column, with the bottom row last.
+                const BITS: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+                let bits = (0..8)
+                    .filter(|i| dots & (1 << i) != 0)
+                    .fold(0, |bits, i| bits | BITS[i]);
+                std::char::from_u32(0x2800 + bits).unwrap_or(' ')
+            }
+            Glyphs::Blocks => match dots & 0b11 {
+                0b01 => '▀',
+                0b10 => '▄',
+                0b11 => '█',
+                _ => ' ',
+            },
+        }
+    }
+}
+
+impl std::str::FromStr for Glyphs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "braille" => Ok(Glyphs::Braille),
+            "blocks" => Ok(Glyphs::Blocks),
+            _ => Err(format!("Unknown glyphs: {}", s)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Cell {
+    dots: u8,
+    /// Colour of the last dot set.
+    color: Option<Color>,
+    /// Faint fills, like the glow of suns, with their combined opacity.
+    shade: Option<(Color, f64)>,
+    text: Option<char>,
+}
+
+/// Draws the scene as text for terminals, e.g. to watch a run over SSH.
+///
+/// The viewport is scaled to fit the characters, keeping its aspect ratio. Every character holds
+/// a few dots, see [`Glyphs`](./enum.Glyphs.html), and the colour last drawn into it. Faint fills
+/// don't set dots but shade the background instead, so suns stand out by their glow. Without
+/// colours, shaded characters are shown as `·`.
+///
+/// ```
+/// use liniverse::{Color, Point, Renderer, Terminal};
+///
+/// let mut terminal = Terminal::new(4, 1, (8.0, 4.0)).with_color(false);
+/// terminal.fill_rect(Point::new(0.0, 0.0), 2.0, 4.0, Color::WHITE);
+/// assert_eq!(terminal.to_string(), "⣿⠀⠀⠀\n");
+/// ```
+pub struct Terminal {
+    columns: usize,
+    rows: usize,
+    glyphs: Glyphs,
+    color: bool,
+    viewport: (f64, f64),
+    /// Dots per pixel of the viewport.
+    scale: f64,
+    /// Position of the viewport's top left corner in dots.
+    offset: Point,
+    alpha: f64,
+    cells: Vec<Cell>,
+}
+
+impl Terminal {
+    /// Draw a viewport of `viewport` pixels into `columns` by `rows` characters, in braille and
+    /// 24-bit colour.
+    pub fn new(columns: usize, rows: usize, viewport: (f64, f64)) -> Self {
+        let mut terminal = Self {
+            columns,
+            rows,
+            glyphs: Glyphs::Braille,
+            color: true,
+            viewport,
+            scale: 1.0,
+            offset: Point::new(0.0, 0.0),
+            alpha: 1.0,
+            cells: vec![Cell::default(); columns * rows],
+        };
+        terminal.fit();
+        terminal
+    }
+
+    pub fn with_glyphs(mut self, glyphs: Glyphs) -> Self {
+        self.glyphs = glyphs;
+        self.fit();
+        self.clear();
+        self
+    }
+
+    /// Whether to colour characters with ANSI escape codes.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Size of the grid of dots.
+    fn dots(&self) -> (usize, usize) {
+        let (x, y) = self.glyphs.dots();
+        (self.columns * x, self.rows * y)
+    }
+
+    /// Scale the viewport to the dots and center it.
+    fn fit(&mut self) {
+        let (width, height) = self.dots();
+        let (width, height) = (width as f64, height as f64);
+        let (vw, vh) = self.viewport;
+        self.scale = if vw > 0.0 && vh > 0.0 {
+            (width / vw).min(height / vh)
+        } else {
+            1.0
+        };
+        self.offset = Point::new(
+            (width - vw * self.scale) / 2.0,
+            (height - vh * self.scale) / 2.0,
+        );
+    }
+
+    /// A position in pixels in dots.
+    fn to_dots(&self, p: Point) -> Point {
+        Point::new(
+            p.x * self.scale + self.offset.x,
+            p.y * self.scale + self.offset.y,
+        )
+    }
+
+    /// Set the dot at `x`, `y`, or shade its character for faint fills.
+    fn mark(&mut self, x: f64, y: f64, color: Color, fill: bool) {
+        let (width, height) = self.dots();
+        if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let (dx, dy) = self.glyphs.dots();
+        let cell = &mut self.cells[y / dy * self.columns + x / dx];
+
+        if fill && self.alpha < 0.5 {
+            let alpha = match cell.shade {
+                Some((_, a)) => a + (1.0 - a) * self.alpha,
+                None => self.alpha,
+            };
+            cell.shade = Some((color, alpha));
+        } else if self.alpha >= 0.1 {
+            cell.dots |= 1 << ((y % dy) * dx + x % dx);
+            cell.color = Some(color);
+        }
+    }
+
+    /// Fill the dots whose centers are within the bounds of `points` and pass `inside`. Sets the
+    /// dot under the first point if no dot does, so tiny shapes don't disappear.
+    fn fill(&mut self, points: &[Point], color: Color, inside: impl Fn(Point) -> bool) {
+        if points.is_empty() {
+            return;
+        }
+        let (min, max) = points.iter().fold((points[0], points[0]), |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+
+        let (width, height) = self.dots();
+        let max = Point::new(max.x.min(width as f64), max.y.min(height as f64));
+        let mut any = false;
+        let mut y = min.y.floor().max(0.0);
+        while y <= max.y {
+            let mut x = min.x.floor().max(0.0);
+            while x <= max.x {
+                if inside(Point::new(x + 0.5, y + 0.5)) {
+                    self.mark(x, y, color, true);
+                    any = true;
+                }
+                x += 1.0;
+            }
+            y += 1.0;
+        }
+        if !any {
+            self.mark(points[0].x, points[0].y, color, true);
+        }
+    }
+
+    /// Set the dots along a line from `a` to `b`, all in dots.
+    fn segment(&mut self, a: Point, b: Point, color: Color) {
+        // Clip the line to the dots first, so lines far off screen don't take long.
+        let (width, height) = self.dots();
+        let (mut t0, mut t1) = (0.0, 1.0);
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        for (p, q) in [
+            (-dx, a.x + 1.0),
+            (dx, width as f64 + 1.0 - a.x),
+            (-dy, a.y + 1.0),
+            (dy, height as f64 + 1.0 - a.y),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return;
+                }
+            } else if p < 0.0 {
+                t0 = f64::max(t0, q / p);
+            } else {
+                t1 = f64::min(t1, q / p);
+            }
+        }
+        if t0 > t1 {
+            return;
+        }
+        let (a, b) = (
+            Point::new(a.x + dx * t0, a.y + dy * t0),
+            Point::new(a.x + dx * t1, a.y + dy * t1),
+        );
+
+        let steps = (2.0 * (b.x - a.x).abs().max((b.y - a.y).abs()))
+            .ceil()
+            .max(1.0);
+        for i in 0..=steps as usize {
+            let t = i as f64 / steps;
+            let p = Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+            self.mark(p.x, p.y, color, false);
+        }
+    }
+
+    /// Write the ANSI escape code switching to `fg` and `bg`, if they differ from `current`.
+    fn switch(
+        &self,
+        f: &mut std::fmt::Formatter,
+        current: &mut (Option<Color>, Option<Color>),
+        fg: Option<Color>,
+        bg: Option<Color>,
+    ) -> std::fmt::Result {
+        if !self.color || *current == (fg, bg) {
+            return Ok(());
+        }
+        write!(f, "\x1b[0m")?;
+        if let Some(c) = fg {
+            write!(f, "\x1b[38;2;{};{};{}m", c.r, c.g, c.b)?;
+        }
+        if let Some(c) = bg {
+            write!(f, "\x1b[48;2;{};{};{}m", c.r, c.g, c.b)?;
+        }
+        *current = (fg, bg);
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Terminal {
+    /// Write the rows of characters, each ending in a newline.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for row in self.cells.chunks(self.columns.max(1)) {
+            let mut current = (None, None);
+            for cell in row {
+                let bg = cell
+                    .shade
+                    .map(|(color, alpha)| Color::BLACK.lerp(color, alpha.min(1.0)));
+                let c = match (cell.text, cell.dots) {
+                    (Some(c), _) => c,
+                    (None, 0) if cell.shade.is_some() && !self.color => '·',
+                    (None, dots) => self.glyphs.glyph(dots),
+                };
+                self.switch(f, &mut current, cell.color, bg)?;
+                write!(f, "{}", c)?;
+            }
+            self.switch(f, &mut current, None, None)?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for Terminal {
+    fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = Cell::default();
+        }
+    }
+
+    fn set_alpha(&mut self, alpha: f64) {
+        self.alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    fn fill_circle(&mut self, center: Point, radius: f64, color: Color) {
+        let (center, radius) = (self.to_dots(center), radius * self.scale);
+        let points = [
+            center,
+            Point::new(center.x - radius, center.y - radius),
+            Point::new(center.x + radius, center.y + radius),
+        ];
+        self.fill(&points, color, |p| {
+            (p.x - center.x).powi(2) + (p.y - center.y).powi(2) <= radius * radius
+        });
+    }
+
+    /// Outlines are one dot wide, whatever their width.
+    fn stroke_circle(&mut self, center: Point, radius: f64, _width: f64, color: Color) {
+        let (center, radius) = (self.to_dots(center), radius * self.scale);
+        let steps = (2.0 * PI * radius).ceil().clamp(8.0, 4096.0) as usize;
+        let points: Vec<Point> = (0..=steps)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / steps as f64;
+                Point::new(
+                    center.x + radius * angle.cos(),
+                    center.y + radius * angle.sin(),
+                )
+            })
+            .collect();
+        for pair in points.windows(2) {
+            self.segment(pair[0], pair[1], color);
+        }
+    }
+
+    fn stroke_line(&mut self, points: &[Point], _width: f64, color: Color) {
+        let points: Vec<Point> = points.iter().map(|p| self.to_dots(*p)).collect();
+        match points.as_slice() {
+            [p] => self.mark(p.x, p.y, color, false),
+            _ => {
+                for pair in points.windows(2) {
+                    self.segment(pair[0], pair[1], color);
+                }
+            }
+        }
+    }
+
+    fn fill_polygon(&mut self, points: &[Point], color: Color) {
+        let points: Vec<Point> = points.iter().map(|p| self.to_dots(*p)).collect();
+        let inside = |p: Point| points.len() > 2 && contains(&points, p);
+        self.fill(&points, color, inside);
+    }
+
+    /// Text is written one character per cell, starting in the cell holding `pos`.
+    fn fill_text(&mut self, text: &str, pos: Point, color: Color) {
+        if self.alpha <= 0.0 {
+            return;
+        }
+        let pos = self.to_dots(pos);
+        let (dx, dy) = self.glyphs.dots();
+        if pos.x < 0.0 || pos.y < 1.0 {
+            return;
+        }
+        // The baseline is at the bottom of the text, so go up into the cell above it.
+        let (column, row) = (pos.x as usize / dx, (pos.y - 1.0) as usize / dy);
+        if row >= self.rows {
+            return;
+        }
+        for (i, c) in text.chars().enumerate() {
+            if column + i >= self.columns {
+                break;
+            }
+            let cell = &mut self.cells[row * self.columns + column + i];
+            cell.text = Some(c);
+            cell.color = Some(color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glyphs() {
+        let mut terminal = Terminal::new(2, 1, (4.0, 4.0)).with_color(false);
+        terminal.stroke_line(
+            &[Point::new(0.5, 0.5), Point::new(0.5, 3.5)],
+            1.0,
+            Color::WHITE,
+        );
+        assert_eq!(terminal.to_string(), "⡇⠀\n");
+
+        let mut terminal = terminal.with_glyphs(Glyphs::Blocks);
+        terminal.fill_circle(Point::new(1.0, 1.0), 0.5, Color::WHITE);
+        assert_eq!(terminal.to_string(), "▀ \n");
+        terminal.fill_text("ab", Point::new(2.0, 4.0), Color::WHITE);
+        assert_eq!(terminal.to_string(), "▀a\n");
+    }
+
+    #[test]
+    fn universe() {
+        let mut universe = Universe::new((80.0, 40.0));
+        universe.add(Planet::new_sun(40.0, 20.0));
+        let mut terminal = Terminal::new(40, 10, (80.0, 40.0)).with_color(false);
+        universe.render(&mut terminal);
+        let text = terminal.to_string();
+        assert_eq!(text.lines().count(), 10);
+        assert!(text.lines().all(|l| l.chars().count() == 40));
+        // The sun is surrounded by its glow.
+        assert!(text.contains('·'));
+        assert!(text.contains('⣿'));
+
+        let mut terminal = Terminal::new(40, 10, (80.0, 40.0));
+        universe.render(&mut terminal);
+        let sun = Color::SUN;
+        assert!(terminal
+            .to_string()
+            .contains(&format!("\x1b[38;2;{};{};{}m", sun.r, sun.g, sun.b)));
+    }
+}
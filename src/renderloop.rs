@@ -22,6 +22,10 @@ pub struct RenderLoop {
     frames: Vec<f64>,
     play_pause_btn: web_sys::HtmlElement,
     prev_timestamp: f64,
+    /// JS functions called with each event of the universe.
+    listeners: Vec<js_sys::Function>,
+    /// Records every drawn frame while set.
+    recorder: Option<Recorder>,
 }
 
 impl RenderLoop {
@@ -43,6 +47,8 @@ impl RenderLoop {
             closure: None,
             frames: Vec::new(),
             prev_timestamp: 0.0,
+            listeners: Vec::new(),
+            recorder: None,
         }
     }
 }
@@ -74,15 +80,21 @@ impl RenderLoop {
                 ));
             }
 
-            self.universe
-                .borrow_mut()
-                .tick_n_draw_brute(&self.context, delta);
+            self.universe.borrow_mut().tick_n_draw(&self.context, delta);
         }
         //let mean = self.fps.iter().fold(0.0, |acc, curr| acc + curr);
 
         self.universe
             .borrow_mut()
-            .tick_n_draw_brute(&self.context, delta / 50.0);
+            .tick_n_draw(&self.context, delta / 50.0);
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.capture(&self.universe.borrow()) {
+                web_sys::console::error_1(&e.as_str().into());
+                self.recorder = None;
+            }
+        }
+        show_inspector(&self.document, &self.universe.borrow());
+        bindings::dispatch(&mut self.universe.borrow_mut(), &self.listeners);
 
         self.animation_id = if let Some(ref closure) = self.closure {
             Some(
@@ -95,10 +107,29 @@ impl RenderLoop {
         }
     }
 
+    pub fn universe(&self) -> std::cell::Ref<'_, Universe> {
+        self.universe.borrow()
+    }
+
     pub fn replace_universe(&mut self, universe: Universe) {
         let _ = self.universe.replace(universe);
     }
 
+    pub fn subscribe(&mut self, listener: js_sys::Function) {
+        self.listeners.push(listener);
+    }
+
+    /// Record the frames drawn from now on into an animation. Drops any recording in progress.
+    pub fn start_recording(&mut self, cfg: AnimationConfig) -> Result<(), JsValue> {
+        self.recorder = Some(Recorder::new(cfg).map_err(|e| JsValue::from(e.as_str()))?);
+        Ok(())
+    }
+
+    /// Stop recording and hand out the animation, if recording.
+    pub fn stop_recording(&mut self) -> Option<Result<Vec<u8>, String>> {
+        self.recorder.take().map(Recorder::finish)
+    }
+
     pub fn is_running(&self) -> bool {
         self.animation_id.is_some()
     }
@@ -134,8 +165,8 @@ impl RenderLoop {
             self.frames.remove(0);
         }
 
-        let mut min = std::f64::INFINITY;
-        let mut max = std::f64::NEG_INFINITY;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
         let mut sum = 0.0;
         for f in &self.frames {
             min = f.min(min);
@@ -0,0 +1,266 @@
+use rand::distributions::StandardNormal;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+
+use super::*;
+use crate::units::SOLAR_MASS;
+
+/// One kiloparsec in m.
+const KILOPARSEC: f64 = 3.085_677_581e19;
+
+/// Width and height of the galaxy universe in kpc.
+const DIMENSIONS: (f64, f64) = (48.0, 48.0);
+
+/// Softening length of gravity in the galaxy universe in kpc. Each body stands for a whole
+/// cloud of stars, which pass through each other rather than scatter off sharply.
+const SOFTENING: f64 = 0.2;
+
+/// How a disk galaxy is laid out by [`galaxy_disk`](./fn.galaxy_disk.html).
+///
+/// Lengths, masses and velocities are in the units of the universe the disk is added to. For
+/// [`galaxy`](./fn.galaxy.html), that's kpc and 10¹⁰ solar masses, with G = 1 and a velocity
+/// unit of about 207 km/s.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GalaxyConfig {
+    /// Number of bodies, or tracers, making up the disk.
+    pub bodies: usize,
+    /// Mass of the body at the center, a bulge or black hole. It's pinned in place.
+    pub central_mass: f64,
+    /// Radius of the body at the center. The disk starts right outside of it.
+    pub central_radius: f64,
+    /// Total mass of the disk, shared evenly by its bodies. Tracers are massless, so it's
+    /// ignored for them.
+    pub disk_mass: f64,
+    /// Scale length of the exponential surface density Σ(r) ∝ exp(-r / scale_length).
+    pub scale_length: f64,
+    /// Radius the disk is cut off at.
+    pub radius: f64,
+    /// Radius of every body of the disk. Bodies that touch merge, so it's kept far below their
+    /// spacing.
+    pub body_radius: f64,
+    /// Random velocity added to every body, relative to the speed of its circular orbit. The
+    /// disk is cold at 0, with every body on a circular orbit, and gets hotter as it grows.
+    pub dispersion: f64,
+    /// A dark matter halo holding the disk together along with its own mass. It's centered on
+    /// the galaxy, wherever its own center was.
+    pub halo: Option<Halo>,
+    /// Whether the disk is made of massless tracers rather than planets.
+    pub tracers: bool,
+    /// Seed of the random positions and velocities, so the same config always builds the same
+    /// galaxy.
+    pub seed: u64,
+}
+
+impl std::default::Default for GalaxyConfig {
+    /// A disk of 500 bodies weighing 10¹⁰ solar masses around a bulge of as much, in a halo
+    /// with a rotation curve flattening out at about 200 km/s. The halo dominates, so the disk
+    /// stays calm for many rotations.
+    fn default() -> Self {
+        Self {
+            bodies: 500,
+            central_mass: 1.0,
+            central_radius: 0.5,
+            disk_mass: 1.0,
+            scale_length: 3.0,
+            radius: 15.0,
+            body_radius: 0.0001,
+            dispersion: 0.1,
+            halo: Some(Halo::new(Point::default(), 1.0, 3.0)),
+            tracers: false,
+            seed: 0,
+        }
+    }
+}
+
+impl GalaxyConfig {
+    /// The massive bodies' share of the disk's mass within `r` of the center, for the
+    /// exponential profile cut off at `radius`. Without any massive bodies, that's none.
+    fn disk_mass_within(&self, r: f64) -> f64 {
+        if self.tracers {
+            return 0.0;
+        }
+        // Mass within x = r / scale_length of an untruncated exponential disk, as a share of
+        // its total.
+        let share = |x: f64| 1.0 - (1.0 + x) * (-x).exp();
+        let r = r.min(self.radius);
+        self.disk_mass * share(r / self.scale_length) / share(self.radius / self.scale_length)
+    }
+
+    /// Speed of a circular orbit at a distance of `r` from the center, for a gravitational
+    /// constant of `g` and gravity softened by `softening`. Mass within `r` pulls as if it was
+    /// all at the center, the halo adds its own pull.
+    pub fn circular_velocity(&self, r: f64, g: f64, softening: f64) -> f64 {
+        let enclosed = self.central_mass + self.disk_mass_within(r);
+        let pull = g * enclosed * r / (r * r + softening * softening).powf(1.5);
+        let halo = self.halo.map_or(0.0, |h| h.circular_velocity(r).powi(2));
+        (pull * r + halo).sqrt()
+    }
+}
+
+/// Build a [`Universe`](../universe/struct.Universe.html) holding a disk galaxy in the middle,
+/// measured in kpc and 10¹⁰ solar masses, with G = 1 and a time unit of about 4.7 million years.
+///
+/// The galaxy runs on the [`BarnesHut`](../solver/enum.Solver.html#variant.BarnesHut) solver
+/// and the [`Leapfrog`](../solver/enum.Integrator.html#variant.Leapfrog) integrator, which keep
+/// it turning smoothly for many rotations. Gravity is softened, and bodies flung out of the
+/// galaxy escape.
+pub fn galaxy(viewport: (f64, f64), cfg: &GalaxyConfig) -> Universe {
+    let units = UnitSystem::nbody(KILOPARSEC, 1e10 * SOLAR_MASS);
+    let mut universe = Universe::with_units(DIMENSIONS, units, 0.02, viewport);
+    universe.set_solver(Solver::BarnesHut);
+    universe.set_integrator(Integrator::Leapfrog);
+    universe.set_boundary(Boundary::Open);
+    universe.set_softening(SOFTENING);
+    let center = Point::new(DIMENSIONS.0 / 2.0, DIMENSIONS.1 / 2.0);
+    galaxy_disk(&mut universe, center, cfg);
+    universe
+}
+
+/// Add a disk galaxy around `center` to `universe`: a pinned central body, the halo if there's
+/// one, and the bodies or tracers of the disk.
+///
+/// Radii are drawn from the exponential surface density, so the number of bodies at a distance r
+/// goes as r·exp(-r / scale_length). Every body is put on a circular orbit held by the mass
+/// enclosed within its radius and the halo, then nudged by a random velocity of the configured
+/// dispersion, along and across its orbit. All of them turn counter-clockwise.
+pub fn galaxy_disk(universe: &mut Universe, center: Point, cfg: &GalaxyConfig) {
+    let (g, softening) = (universe.units().g(), universe.softening());
+    let mut rng = StdRng::seed_from_u64(cfg.seed);
+
+    let central = Planet::with_mass(
+        center,
+        cfg.central_mass,
+        cfg.central_radius,
+        Point::default(),
+    );
+    universe.add(central.into_sun());
+    universe.set_halo(cfg.halo.map(|halo| Halo { center, ..halo }));
+
+    let mass = cfg.disk_mass / cfg.bodies.max(1) as f64;
+    let inner = cfg.central_radius + cfg.body_radius;
+    let mut added = 0;
+    while added < cfg.bodies && inner < cfg.radius {
+        // The sum of two exponentially distributed numbers follows r·exp(-r).
+        let u: f64 = rng.gen_range(f64::EPSILON, 1.0);
+        let w: f64 = rng.gen_range(f64::EPSILON, 1.0);
+        let r = -cfg.scale_length * (u * w).ln();
+        if r <= inner || r > cfg.radius {
+            continue;
+        }
+        let angle = rng.gen_range(0.0, 2.0 * PI);
+        let outward = Point::new(angle.cos(), angle.sin());
+        let along = Point::new(-outward.y, outward.x);
+
+        let speed = cfg.circular_velocity(r, g, softening);
+        let sigma = cfg.dispersion * speed;
+        let velocity = along * (speed + sigma * rng.sample::<f64, _>(StandardNormal))
+            + outward * (sigma * rng.sample::<f64, _>(StandardNormal));
+
+        let pos = center + outward * r;
+        if cfg.tracers {
+            universe.add_particle(pos, velocity);
+        } else {
+            universe.add(Planet::with_mass(pos, mass, cfg.body_radius, velocity));
+        }
+        added += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotation_curve() {
+        let cfg = GalaxyConfig::default();
+        // Beyond the disk, its whole mass pulls along with the central one.
+        let far = cfg.circular_velocity(30.0, 1.0, 0.0).powi(2);
+        let halo = Halo::new(Point::default(), 1.0, 3.0).circular_velocity(30.0);
+        let mass = cfg.central_mass + cfg.disk_mass;
+        assert!((far - mass / 30.0 - halo * halo).abs() < 1e-12);
+        // Tracers only feel the central mass and the halo.
+        let tracers = GalaxyConfig {
+            tracers: true,
+            halo: None,
+            ..cfg
+        };
+        assert!((tracers.circular_velocity(4.0, 1.0, 0.0) - 0.5).abs() < 1e-12);
+        // Softening weakens the pull close in.
+        let softened = tracers.circular_velocity(4.0, 1.0, 3.0).powi(2);
+        assert!((softened - 16.0 / 125.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn stays_a_disk() {
+        // Tracers are only held by the central mass and the halo, so their orbits are exact up to
+        // the dispersion.
+        let cfg = GalaxyConfig {
+            bodies: 300,
+            tracers: true,
+            dispersion: 0.0,
+            ..GalaxyConfig::default()
+        };
+        let mut universe = galaxy((480.0, 480.0), &cfg);
+        assert_eq!(universe.planets().len(), 1);
+        assert_eq!(universe.particles().len(), 300);
+
+        let center = Point::new(24.0, 24.0);
+        let radii = |universe: &Universe| -> Vec<f64> {
+            let positions = universe.particles().positions();
+            positions.iter().map(|p| p.distance_to(center)).collect()
+        };
+        let start = radii(&universe);
+        assert!(start.iter().all(|r| *r <= 15.0));
+
+        // About three rotations at the scale length, with the orbit taking 2πr/v.
+        let period = 2.0 * PI * 3.0 / cfg.circular_velocity(3.0, 1.0, SOFTENING);
+        let ticks = (3.0 * period / universe.dt()) as usize;
+        for _ in 0..ticks {
+            universe.tick();
+        }
+        assert_eq!(universe.particles().len(), 300);
+        for (before, after) in start.iter().zip(radii(&universe)) {
+            assert!((after - before).abs() < 0.01 * before);
+        }
+
+        // A self-gravitating disk of planets heats up, but keeps its size and shape for several
+        // rotations, turning all along, with hardly any of its bodies merging.
+        let cfg = GalaxyConfig {
+            bodies: 100,
+            ..GalaxyConfig::default()
+        };
+        let mut universe = galaxy((480.0, 480.0), &cfg);
+        let shape = |universe: &Universe| {
+            let disk: Vec<_> = universe.planets()[1..].iter().map(|p| p.borrow()).collect();
+            let mut radii: Vec<f64> = disk.iter().map(|p| p.pos().distance_to(center)).collect();
+            radii.sort_by(f64::total_cmp);
+            let half = radii[radii.len() / 2];
+            let outer = radii[radii.len() * 9 / 10];
+            // Angular momentum relative to that of bodies all on circular orbits at their speed.
+            let (spin, circular) = disk.iter().fold((0.0, 0.0), |(spin, circular), p| {
+                let (r, v) = (p.pos() - center, Newtonian::velocity(&**p));
+                (spin + r.x * v.y - r.y * v.x, circular + r.mag() * v.mag())
+            });
+            let offset = disk
+                .iter()
+                .fold(Point::default(), |acc, p| acc + (p.pos() - center));
+            (
+                half,
+                outer,
+                spin / circular,
+                offset.mag() / disk.len() as f64,
+            )
+        };
+        let (half, outer, _, _) = shape(&universe);
+        for _ in 0..(5.0 * period / universe.dt()) as usize {
+            universe.tick();
+        }
+        assert!(universe.planets().len() > 90);
+        let (half_after, outer_after, rotation, offset) = shape(&universe);
+        assert!((half_after - half).abs() < 0.2 * half);
+        assert!((outer_after - outer).abs() < 0.25 * outer);
+        assert!(rotation > 0.95);
+        assert!(offset < 0.5 * cfg.scale_length);
+    }
+}
@@ -0,0 +1,40 @@
+//! Generators for the initial conditions of a [`Universe`](../universe/struct.Universe.html).
+use super::*;
+
+mod solar_system;
+pub use solar_system::*;
+mod galaxy;
+pub use galaxy::*;
+
+/// Names of the scenarios [`by_name`](./fn.by_name.html) knows about.
+pub const NAMES: [&str; 5] = [
+    "random",
+    "solar-system",
+    "solar-system-moons",
+    "galaxy",
+    "galaxy-tracers",
+];
+
+/// Create the scenario called `name`, one of [`NAMES`](./constant.NAMES.html), drawn onto a
+/// canvas of `viewport` pixels.
+pub fn by_name(name: &str, viewport: (f64, f64)) -> Option<Universe> {
+    match name {
+        "random" => {
+            let mut universe = Universe::new(viewport);
+            universe.init_random();
+            Some(universe)
+        }
+        "solar-system" => Some(solar_system(viewport, false)),
+        "solar-system-moons" => Some(solar_system(viewport, true)),
+        "galaxy" => Some(galaxy(viewport, &GalaxyConfig::default())),
+        "galaxy-tracers" => {
+            let cfg = GalaxyConfig {
+                bodies: 20_000,
+                tracers: true,
+                ..GalaxyConfig::default()
+            };
+            Some(galaxy(viewport, &cfg))
+        }
+        _ => None,
+    }
+}
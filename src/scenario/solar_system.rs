@@ -0,0 +1,327 @@
+use super::*;
+use crate::units::{AU, SOLAR_MASS};
+
+/// Keplerian elements of a body at epoch J2000, projected onto the ecliptic plane.
+struct Elements {
+    /// Semi-major axis in m.
+    a: f64,
+    /// Eccentricity.
+    e: f64,
+    /// Longitude of periapsis ϖ in degrees.
+    long_peri: f64,
+    /// Mean longitude L in degrees.
+    mean_long: f64,
+    /// Mass in kg.
+    mass: f64,
+    /// Mean radius in m.
+    radius: f64,
+    color: Option<Color>,
+}
+
+const KM: f64 = 1_000.0;
+
+// Sun
+const SUN_MASS: f64 = SOLAR_MASS;
+const SUN_RADIUS: f64 = 695_700.0 * KM;
+
+// The planets, taken from "Keplerian Elements for Approximate Positions of the Major Planets"
+// (E M Standish, JPL) and the NASA planetary fact sheets. Earth's elements are those of the
+// Earth-Moon barycenter.
+const PLANETS: [Elements; 8] = [
+    // Mercury
+    Elements {
+        a: 0.387_099_27 * AU,
+        e: 0.205_635_93,
+        long_peri: 77.457_796_28,
+        mean_long: 252.250_323_50,
+        mass: 3.3011e23,
+        radius: 2_439.7 * KM,
+        color: Some(Color::new(169, 169, 169)),
+    },
+    // Venus
+    Elements {
+        a: 0.723_335_66 * AU,
+        e: 0.006_776_72,
+        long_peri: 131.602_467_18,
+        mean_long: 181.979_099_50,
+        mass: 4.8675e24,
+        radius: 6_051.8 * KM,
+        color: Some(Color::new(230, 200, 140)),
+    },
+    // Earth
+    Elements {
+        a: 1.000_002_61 * AU,
+        e: 0.016_711_23,
+        long_peri: 102.937_681_93,
+        mean_long: 100.464_571_66,
+        mass: 5.9722e24,
+        radius: 6_371.0 * KM,
+        color: Some(Color::new(70, 130, 220)),
+    },
+    // Mars
+    Elements {
+        a: 1.523_710_34 * AU,
+        e: 0.093_394_10,
+        long_peri: -23.943_629_59,
+        mean_long: -4.553_432_05,
+        mass: 6.4171e23,
+        radius: 3_389.5 * KM,
+        color: Some(Color::new(200, 80, 50)),
+    },
+    // Jupiter
+    Elements {
+        a: 5.202_887_00 * AU,
+        e: 0.048_386_24,
+        long_peri: 14.728_479_83,
+        mean_long: 34.396_440_51,
+        mass: 1.898_19e27,
+        radius: 69_911.0 * KM,
+        color: Some(Color::new(210, 170, 120)),
+    },
+    // Saturn
+    Elements {
+        a: 9.536_675_94 * AU,
+        e: 0.053_861_79,
+        long_peri: 92.598_878_31,
+        mean_long: 49.954_244_23,
+        mass: 5.6834e26,
+        radius: 58_232.0 * KM,
+        color: Some(Color::new(225, 200, 130)),
+    },
+    // Uranus
+    Elements {
+        a: 19.189_164_64 * AU,
+        e: 0.047_257_44,
+        long_peri: 170.954_276_30,
+        mean_long: 313.238_104_51,
+        mass: 8.6810e25,
+        radius: 25_362.0 * KM,
+        color: Some(Color::new(150, 210, 220)),
+    },
+    // Neptune
+    Elements {
+        a: 30.069_922_76 * AU,
+        e: 0.008_590_48,
+        long_peri: 44.964_762_27,
+        mean_long: -55.120_029_69,
+        mass: 1.024_13e26,
+        radius: 24_622.0 * KM,
+        color: Some(Color::new(70, 100, 210)),
+    },
+];
+
+// Major moons as (index into `PLANETS`, elements relative to their planet). Only the Moon's
+// phase is bundled, the others start out at periapsis.
+const MOONS: [(usize, Elements); 6] = [
+    // Moon
+    (
+        2,
+        Elements {
+            a: 384_400.0 * KM,
+            e: 0.0549,
+            long_peri: 83.353,
+            mean_long: 218.316,
+            mass: 7.342e22,
+            radius: 1_737.4 * KM,
+            color: None,
+        },
+    ),
+    // Io
+    (
+        4,
+        Elements {
+            a: 421_700.0 * KM,
+            e: 0.0041,
+            long_peri: 0.0,
+            mean_long: 0.0,
+            mass: 8.9319e22,
+            radius: 1_821.6 * KM,
+            color: None,
+        },
+    ),
+    // Europa
+    (
+        4,
+        Elements {
+            a: 671_034.0 * KM,
+            e: 0.009,
+            long_peri: 90.0,
+            mean_long: 90.0,
+            mass: 4.7998e22,
+            radius: 1_560.8 * KM,
+            color: None,
+        },
+    ),
+    // Ganymede
+    (
+        4,
+        Elements {
+            a: 1_070_412.0 * KM,
+            e: 0.0013,
+            long_peri: 180.0,
+            mean_long: 180.0,
+            mass: 1.4819e23,
+            radius: 2_634.1 * KM,
+            color: None,
+        },
+    ),
+    // Callisto
+    (
+        4,
+        Elements {
+            a: 1_882_709.0 * KM,
+            e: 0.0074,
+            long_peri: 270.0,
+            mean_long: 270.0,
+            mass: 1.0759e23,
+            radius: 2_410.3 * KM,
+            color: None,
+        },
+    ),
+    // Titan
+    (
+        5,
+        Elements {
+            a: 1_221_870.0 * KM,
+            e: 0.0288,
+            long_peri: 0.0,
+            mean_long: 0.0,
+            mass: 1.3452e23,
+            radius: 2_574.7 * KM,
+            color: None,
+        },
+    ),
+];
+
+/// Width and height of the solar system universe in AU, roomy enough for Neptune's orbit.
+const DIMENSIONS: (f64, f64) = (64.0, 64.0);
+
+/// Build a [`Universe`](../universe/struct.Universe.html) holding our solar system in
+/// [`astronomical`](../units/struct.UnitSystem.html#method.astronomical) units, with the Sun at
+/// its center and stepping a ten thousandth of a year per tick.
+pub fn solar_system(viewport: (f64, f64), with_moons: bool) -> Universe {
+    let units = UnitSystem::astronomical();
+    let mut universe = Universe::with_units(DIMENSIONS, units, 1e-4, viewport);
+    let center = Point::new(DIMENSIONS.0 / 2.0, DIMENSIONS.1 / 2.0);
+    for planet in solar_system_planets(&units, center, with_moons) {
+        universe.add(planet);
+    }
+    universe
+}
+
+/// Create the Sun at `center`, followed by the eight planets and, optionally, their major moons.
+///
+/// The bundled J2000 elements are converted into 2D state vectors in the ecliptic plane. Since the
+/// Sun is kept in place by the [`Universe`](../universe/struct.Universe.html), planets orbit it
+/// with its mass alone, while a moon and its planet orbit their common barycenter.
+pub fn solar_system_planets(units: &UnitSystem, center: Point, with_moons: bool) -> Vec<Planet> {
+    let g = units.g();
+    let sun_mass = units.mass_from_si(SUN_MASS);
+
+    let mut planets = vec![Planet::with_mass(
+        center,
+        sun_mass,
+        units.length_from_si(SUN_RADIUS),
+        Point::default(),
+    )
+    .into_sun()];
+
+    for (i, el) in PLANETS.iter().enumerate() {
+        let (bary_pos, bary_vel) = state_vectors(units, el, g * sun_mass);
+        let mass = units.mass_from_si(el.mass);
+
+        let moons: Vec<&Elements> = MOONS
+            .iter()
+            .filter(|(planet, _)| with_moons && *planet == i)
+            .map(|(_, moon)| moon)
+            .collect();
+        let system_mass = moons
+            .iter()
+            .fold(mass, |acc, moon| acc + units.mass_from_si(moon.mass));
+
+        // The planet is displaced from the barycenter opposite to its moons.
+        let mut pos = bary_pos;
+        let mut vel = bary_vel;
+        let mut satellites = vec![];
+        for moon in moons {
+            let moon_mass = units.mass_from_si(moon.mass);
+            let (rel_pos, rel_vel) = state_vectors(units, moon, g * (mass + moon_mass));
+            pos -= rel_pos * (moon_mass / system_mass);
+            vel -= rel_vel * (moon_mass / system_mass);
+            satellites.push((moon, rel_pos, rel_vel));
+        }
+
+        let mut planet =
+            Planet::with_mass(center + pos, mass, units.length_from_si(el.radius), vel);
+        if let Some(color) = el.color {
+            planet = planet.with_color(color);
+        }
+        planets.push(planet);
+        for (moon, rel_pos, rel_vel) in satellites {
+            planets.push(Planet::with_mass(
+                center + pos + rel_pos,
+                units.mass_from_si(moon.mass),
+                units.length_from_si(moon.radius),
+                vel + rel_vel,
+            ));
+        }
+    }
+
+    planets
+}
+
+/// Position and velocity relative to the central body for a set of elements, with `mu` being
+/// the gravitational parameter of the orbit.
+fn state_vectors(units: &UnitSystem, el: &Elements, mu: f64) -> (Point, Point) {
+    let orbit = Orbit::from_mean_anomaly(
+        units.length_from_si(el.a),
+        el.e,
+        el.long_peri.to_radians(),
+        (el.mean_long - el.long_peri).to_radians(),
+        false,
+    );
+    orbit.to_state(mu)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn bodies() {
+        let units = UnitSystem::astronomical();
+        let center = Point::new(32.0, 32.0);
+        assert_eq!(solar_system_planets(&units, center, false).len(), 9);
+
+        let planets = solar_system_planets(&units, center, true);
+        assert_eq!(planets.len(), 15);
+
+        // Earth is roughly 1 AU out, moving at roughly 2π AU per year.
+        let earth = &planets[3];
+        let r = earth.pos().distance_to(center);
+        assert!((r - 1.0).abs() < 0.02, "{}", r);
+        let v = Newtonian::velocity(earth).mag();
+        assert!((v - 2.0 * PI).abs() < 0.15, "{}", v);
+
+        // Relative masses are kept, Jupiter weighing in at about a thousandth of the Sun.
+        let ratio = planets[0].mass() / planets[6].mass();
+        assert!((ratio - 1047.6).abs() < 1.0, "{}", ratio);
+    }
+
+    #[test]
+    fn earth_year() {
+        let mut universe = solar_system((640.0, 640.0), true);
+        let earth = universe.planets()[3].clone();
+        let start = earth.borrow().pos();
+
+        // One Julian year at 10⁴ ticks per year.
+        for _ in 0..10_000 {
+            universe.tick();
+        }
+
+        let end = earth.borrow().pos();
+        assert!(!earth.borrow().dead());
+        assert!(start.distance_to(end) < 0.01, "{}", start.distance_to(end));
+    }
+}
@@ -0,0 +1,72 @@
+/// How the gravitational pull on each planet is worked out on a tick of the
+/// [`Universe`](../universe/struct.Universe.html).
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum Solver {
+    /// Sum up the pull of every other planet. Exact, but the work grows with the square of the
+    /// number of planets.
+    #[default]
+    Direct,
+    /// Let far away groups of planets pull as one from their center of mass, looked up in a
+    /// [`QuadNode`](../quad/struct.QuadNode.html) tree. How far is far enough is set by the
+    /// universe's `theta`.
+    BarnesHut,
+}
+
+impl Solver {
+    /// Kebab-case name, e.g. `barnes-hut`, as taken by `--solver`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solver::Direct => "direct",
+            Solver::BarnesHut => "barnes-hut",
+        }
+    }
+}
+
+impl std::str::FromStr for Solver {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "direct" => Ok(Solver::Direct),
+            "barnes-hut" => Ok(Solver::BarnesHut),
+            _ => Err(format!("Unknown solver: {}", s)),
+        }
+    }
+}
+
+/// How planets are moved along by their accelerations on a tick of the
+/// [`Universe`](../universe/struct.Universe.html).
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum Integrator {
+    /// Semi-implicit Euler: accelerate all planets over the whole tick, then move them. Needs a
+    /// single evaluation of the forces per tick.
+    #[default]
+    Euler,
+    /// Kick-drift-kick leapfrog: accelerate over half a tick, move, then accelerate over the other
+    /// half from where the planets ended up. Costs two evaluations of the forces per tick, but
+    /// keeps the energy of orbits from drifting off over long runs.
+    Leapfrog,
+}
+
+impl Integrator {
+    /// The name a snapshot records the integrator under, so a resumed run keeps stepping the
+    /// same way.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::Euler => "euler",
+            Integrator::Leapfrog => "leapfrog",
+        }
+    }
+}
+
+impl std::str::FromStr for Integrator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "euler" => Ok(Integrator::Euler),
+            "leapfrog" => Ok(Integrator::Leapfrog),
+            _ => Err(format!("Unknown integrator: {}", s)),
+        }
+    }
+}
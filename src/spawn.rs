@@ -0,0 +1,61 @@
+use std::f64::consts::PI;
+
+/// How the velocity of a newly spawned [`Planet`](../planet/struct.Planet.html) is chosen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpawnMode {
+    /// A small random velocity, leaving the planet to fall towards whatever is closest.
+    Random,
+    /// A circular orbit around the dominant attractor.
+    Circular,
+    /// An elliptic orbit around the dominant attractor, starting out at periapsis.
+    Eccentric,
+}
+
+impl std::str::FromStr for SpawnMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(SpawnMode::Random),
+            "circular" => Ok(SpawnMode::Circular),
+            "eccentric" => Ok(SpawnMode::Eccentric),
+            _ => Err(format!("Unknown spawn mode: {}", s)),
+        }
+    }
+}
+
+/// Parameters for planets spawned into a [`Universe`](../universe/struct.Universe.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpawnConfig {
+    pub mode: SpawnMode,
+    /// Mass of the spawned planet in mass units.
+    pub mass: f64,
+    /// Density of the spawned planet in mass units per cubic length unit.
+    pub density: f64,
+    /// Eccentricity of the orbit in [`SpawnMode::Eccentric`](./enum.SpawnMode.html).
+    pub eccentricity: f64,
+    /// Whether the planet orbits clockwise.
+    pub retrograde: bool,
+}
+
+impl SpawnConfig {
+    /// Radius of a sphere with the configured mass and density.
+    pub fn radius(&self) -> f64 {
+        (3.0 * self.mass / (4.0 * PI * self.density)).cbrt()
+    }
+}
+
+impl std::default::Default for SpawnConfig {
+    /// A planet like the ones created by
+    /// [`Planet::new_semi_rng`](../planet/struct.Planet.html#method.new_semi_rng), put on a
+    /// circular orbit.
+    fn default() -> Self {
+        Self {
+            mode: SpawnMode::Circular,
+            mass: 2.5e7,
+            density: 5_000.0,
+            eccentricity: 0.5,
+            retrograde: false,
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! Helpers shared by the tests of several modules.
+
+/// Whether `a` and `b` differ by at most `rel` relative to the larger of the two.
+pub fn close(a: f64, b: f64, rel: f64) -> bool {
+    (a - b).abs() <= rel * a.abs().max(b.abs())
+}
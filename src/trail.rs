@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+
+use super::*;
+
+/// How trails of past positions are recorded and drawn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TrailConfig {
+    /// Number of past positions kept per planet. 0 disables trails.
+    pub length: usize,
+    /// How quickly a trail fades out towards its end. The opacity along the trail goes from 1 at
+    /// the planet to 0 at the oldest position, raised to the power of `fade`, so 0 doesn't fade
+    /// at all and 1 fades linearly.
+    pub fade: f64,
+}
+
+impl TrailConfig {
+    /// Opacity of the trail at `age`, 0 being the newest and 1 the oldest position.
+    pub fn alpha(&self, age: f64) -> f64 {
+        (1.0 - age).max(0.0).powf(self.fade)
+    }
+}
+
+impl std::default::Default for TrailConfig {
+    fn default() -> Self {
+        Self {
+            length: 0,
+            fade: 1.0,
+        }
+    }
+}
+
+/// A ring buffer of a planet's past positions, oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct Trail {
+    points: VecDeque<Point>,
+}
+
+impl Trail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a position, dropping the oldest ones to keep at most `length` of them.
+    pub fn push(&mut self, p: Point, length: usize) {
+        self.points.push_back(p);
+        while self.points.len() > length {
+            self.points.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Point> {
+        self.points.iter()
+    }
+
+    /// Split the trail into continuous runs of `(index, position)`, breaking it wherever the
+    /// planet wrapped around the edges of a universe of the given dimensions.
+    pub fn segments(&self, dimensions: (f64, f64)) -> Vec<Vec<(usize, Point)>> {
+        let mut segments = vec![];
+        let mut current: Vec<(usize, Point)> = vec![];
+
+        for (i, p) in self.points.iter().enumerate() {
+            if let Some((_, prev)) = current.last() {
+                // No planet moves half way across the universe within a single tick, so that's
+                // a wrap-around.
+                let jump = *p - *prev;
+                if jump.x.abs() > dimensions.0 / 2.0 || jump.y.abs() > dimensions.1 / 2.0 {
+                    segments.push(std::mem::take(&mut current));
+                }
+            }
+            current.push((i, *p));
+        }
+
+        if !current.is_empty() {
+            segments.push(current);
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn basics() {
+        let mut trail = Trail::new();
+        for i in 0..10 {
+            trail.push(Point::new(i as f64, 0.0), 4);
+        }
+        assert_eq!(trail.len(), 4);
+        assert_eq!(trail.points().next(), Some(&Point::new(6.0, 0.0)));
+
+        // Shrinking the configured length drops the oldest positions on the next push.
+        trail.push(Point::new(10.0, 0.0), 2);
+        let points: Vec<Point> = trail.points().cloned().collect();
+        assert_eq!(points, vec![Point::new(9.0, 0.0), Point::new(10.0, 0.0)]);
+
+        let cfg = TrailConfig::default();
+        assert_eq!(cfg.alpha(0.0), 1.0);
+        assert_eq!(cfg.alpha(0.25), 0.75);
+        assert_eq!(
+            TrailConfig {
+                length: 1,
+                fade: 0.0
+            }
+            .alpha(0.9),
+            1.0
+        );
+    }
+
+    #[test]
+    fn wrap_around() {
+        let mut trail = Trail::new();
+        for x in &[90.0, 95.0, 99.0, 3.0, 8.0] {
+            trail.push(Point::new(*x, 50.0), 10);
+        }
+
+        let segments = trail.segments((100.0, 100.0));
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].len(), 3);
+        assert_eq!(segments[1][0], (3, Point::new(3.0, 50.0)));
+    }
+}
@@ -0,0 +1,239 @@
+//! Recording the bodies of a [`Universe`](../universe/struct.Universe.html) over a run, to be
+//! analysed elsewhere or replayed later.
+//!
+//! A [`Trajectory`](./struct.Trajectory.html) is written as CSV, with one row per body and frame:
+//!
+//! ```text
+//! step,time,id,x,y,vx,vy,mass,radius,sun,color
+//! 0,0,<id>,400,300,0,0,201061929.8,20,1,#ffc430
+//! ```
+//!
+//! `sun` is `0` or `1`, `color` is a CSS hex colour or `-` for none. Both may be left out.
+use std::f64::consts::PI;
+use uuid::Uuid;
+
+use super::*;
+
+/// The bodies of a universe at one step of a run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrajectoryFrame {
+    pub step: u64,
+    pub time: f64,
+    pub bodies: Vec<PlanetState>,
+}
+
+impl TrajectoryFrame {
+    /// Capture the living planets of `universe` at `step`.
+    pub fn capture(step: u64, universe: &Universe) -> Self {
+        Self {
+            step,
+            time: universe.time(),
+            bodies: universe.snapshot().planets,
+        }
+    }
+}
+
+impl std::fmt::Display for TrajectoryFrame {
+    /// Write the rows of the frame, without the header.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for p in &self.bodies {
+            let color = p.color.map_or("-".to_string(), |c| c.to_css());
+            writeln!(
+                f,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                self.step,
+                self.time,
+                p.id,
+                p.pos.x,
+                p.pos.y,
+                p.velocity.x,
+                p.velocity.y,
+                mass(p),
+                p.radius,
+                p.sun as u8,
+                color
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Frames of a run, in the order they were recorded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trajectory {
+    pub frames: Vec<TrajectoryFrame>,
+}
+
+impl Trajectory {
+    /// The first line of the CSV, naming the columns.
+    pub const HEADER: &'static str = "step,time,id,x,y,vx,vy,mass,radius,sun,color";
+
+    /// The smallest rectangle holding every body of every frame, including their radii.
+    pub fn bounds(&self) -> Option<Rect> {
+        self.frames
+            .iter()
+            .flat_map(|frame| &frame.bodies)
+            .map(|p| Rect::around(p.pos, p.pos).expand(p.radius))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// An empty universe with all frames in view of a `viewport` of that many pixels, to
+    /// [`replay`](../universe/struct.Universe.html#method.replay) them in.
+    pub fn universe(&self, viewport: (f64, f64)) -> Universe {
+        let bounds = self.bounds().unwrap_or_else(|| {
+            Rect::new(viewport.0 / 2.0, viewport.1 / 2.0, viewport.0, viewport.1)
+        });
+        // Leave a little room around the outermost bodies.
+        let bounds = bounds.expand(0.05 * bounds.width().max(bounds.height()));
+        let dimensions = (bounds.width(), bounds.height());
+        let mut universe = Universe::with_units(dimensions, UnitSystem::canvas(), 1.0, viewport);
+        universe.camera_mut().set_center(bounds.center());
+        universe
+    }
+}
+
+impl std::fmt::Display for Trajectory {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{}", Self::HEADER)?;
+        for frame in &self.frames {
+            write!(f, "{}", frame)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Trajectory {
+    type Err = String;
+
+    /// Read the rows of a trajectory. Consecutive rows of the same step make up a frame.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut trajectory = Trajectory::default();
+        let rows = s
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with("step,"));
+
+        for row in rows {
+            let fields: Vec<&str> = row.split(',').collect();
+            if fields.len() != 9 && fields.len() != 11 {
+                return Err(format!("Invalid trajectory row: {}", row));
+            }
+            let number = |i: usize| -> Result<f64, String> {
+                fields[i]
+                    .parse()
+                    .map_err(|_| format!("Invalid trajectory row: {}", row))
+            };
+
+            let step = fields[0]
+                .parse()
+                .map_err(|_| format!("Invalid trajectory row: {}", row))?;
+            let id = Uuid::parse_str(fields[2])
+                .map_err(|_| format!("Invalid body id: {}", fields[2]))?;
+            let (sun, color) = match fields.get(9..11) {
+                Some(&[sun, color]) => (
+                    sun == "1",
+                    if color == "-" {
+                        None
+                    } else {
+                        Some(color.parse()?)
+                    },
+                ),
+                _ => (false, None),
+            };
+            let radius = number(8)?;
+            let body = PlanetState {
+                id,
+                pos: Point::new(number(3)?, number(4)?),
+                velocity: Point::new(number(5)?, number(6)?),
+                density: number(7)? / (4.0 / 3.0 * PI * radius.powi(3)),
+                radius,
+                age: 0.0,
+                merges: 0,
+                pinned: sun,
+                sun,
+                color,
+            };
+
+            match trajectory.frames.last_mut() {
+                Some(frame) if frame.step == step => frame.bodies.push(body),
+                _ => trajectory.frames.push(TrajectoryFrame {
+                    step,
+                    time: number(1)?,
+                    bodies: vec![body],
+                }),
+            }
+        }
+
+        Ok(trajectory)
+    }
+}
+
+fn mass(p: &PlanetState) -> f64 {
+    p.density * 4.0 / 3.0 * PI * p.radius.powi(3)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut universe = scenario::solar_system((800.0, 600.0), false);
+        let mut trajectory = Trajectory::default();
+        for step in 0..3 {
+            trajectory
+                .frames
+                .push(TrajectoryFrame::capture(step, &universe));
+            universe.tick();
+        }
+
+        let read: Trajectory = trajectory.to_string().parse().unwrap();
+        assert_eq!(read.frames.len(), 3);
+        for (a, b) in read.frames.iter().zip(&trajectory.frames) {
+            assert_eq!((a.step, a.time), (b.step, b.time));
+            assert_eq!(a.bodies.len(), b.bodies.len());
+            for (a, b) in a.bodies.iter().zip(&b.bodies) {
+                assert_eq!(
+                    (a.id, a.pos, a.velocity, a.sun),
+                    (b.id, b.pos, b.velocity, b.sun)
+                );
+                assert!((mass(a) - mass(b)).abs() <= 1e-9 * mass(b));
+            }
+        }
+
+        let bounds = read.bounds().unwrap();
+        assert!(read.frames[0].bodies.iter().all(|p| bounds.covers(&p.pos)));
+
+        // Rows without sun and colour still read.
+        let row = "0,0,67e55044-10b1-426f-9247-bb680e5fe0c8,1,2,3,4,5,6\n";
+        assert_eq!(row.parse::<Trajectory>().unwrap().frames.len(), 1);
+        assert!("0,0,nope,1,2,3,4,5,6".parse::<Trajectory>().is_err());
+    }
+
+    #[test]
+    fn replay() {
+        let mut universe = scenario::solar_system((800.0, 600.0), false);
+        let mut trajectory = Trajectory::default();
+        for step in 0..5 {
+            trajectory
+                .frames
+                .push(TrajectoryFrame::capture(step, &universe));
+            universe.tick();
+        }
+
+        let mut replayed = trajectory.universe((200.0, 150.0));
+        let mut trails = replayed.trail_config();
+        trails.length = 10;
+        replayed.set_trail_config(trails);
+        for frame in &trajectory.frames {
+            replayed.replay(frame);
+        }
+        assert_eq!(replayed.time(), trajectory.frames[4].time);
+        assert_eq!(replayed.planets().len(), trajectory.frames[4].bodies.len());
+        for p in replayed.planets() {
+            assert_eq!(p.borrow().trail().len(), 5);
+            let screen = replayed.camera().world_to_screen(p.borrow().pos());
+            assert!(screen.x >= 0.0 && screen.x <= 200.0 && screen.y >= 0.0 && screen.y <= 150.0);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! The physical units the simulation is expressed in.
+//!
+//! Positions, masses and times inside a [`Universe`](../universe/struct.Universe.html) are plain
+//! `f64`s. A [`UnitSystem`](./struct.UnitSystem.html) states how much one of those units is worth
+//! in SI, which gives the gravitational constant its value and lets us convert figures from and to
+//! the real world.
+
+/// Gravitational constant G in m³/(kg·s²).
+pub const G_SI: f64 = 6.674_30e-11;
+/// One astronomical unit in m.
+pub const AU: f64 = 1.495_978_707e11;
+/// One solar mass in kg.
+pub const SOLAR_MASS: f64 = 1.988_47e30;
+/// One Julian year in s.
+pub const YEAR: f64 = 365.25 * 86_400.0;
+
+/// A set of scales for length, mass and time, each given in its SI unit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UnitSystem {
+    /// Length of one unit in m.
+    length: f64,
+    /// Mass of one unit in kg.
+    mass: f64,
+    /// Duration of one unit in s.
+    time: f64,
+}
+
+impl UnitSystem {
+    pub fn new(length: f64, mass: f64, time: f64) -> Self {
+        Self { length, mass, time }
+    }
+
+    /// Metres, kilograms and seconds.
+    pub fn si() -> Self {
+        Self::new(1.0, 1.0, 1.0)
+    }
+
+    /// Astronomical units, solar masses and Julian years. G comes out at roughly 4π².
+    pub fn astronomical() -> Self {
+        Self::new(AU, SOLAR_MASS, YEAR)
+    }
+
+    /// N-body units for the given length and mass scales: time is chosen so that G = 1.
+    pub fn nbody(length: f64, mass: f64) -> Self {
+        let time = (length.powi(3) / (G_SI * mass)).sqrt();
+        Self::new(length, mass, time)
+    }
+
+    /// The units the canvas simulation has always been running in: a metre per pixel, kilograms
+    /// and one tick lasting 100 s.
+    pub fn canvas() -> Self {
+        Self::new(1.0, 1.0, 100.0)
+    }
+
+    /// The gravitational constant expressed in this unit system.
+    pub fn g(&self) -> f64 {
+        G_SI * self.mass * self.time.powi(2) / self.length.powi(3)
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn length_to_si(&self, l: f64) -> f64 {
+        l * self.length
+    }
+
+    pub fn length_from_si(&self, l: f64) -> f64 {
+        l / self.length
+    }
+
+    pub fn mass_to_si(&self, m: f64) -> f64 {
+        m * self.mass
+    }
+
+    pub fn mass_from_si(&self, m: f64) -> f64 {
+        m / self.mass
+    }
+
+    pub fn time_to_si(&self, t: f64) -> f64 {
+        t * self.time
+    }
+
+    pub fn time_from_si(&self, t: f64) -> f64 {
+        t / self.time
+    }
+
+    pub fn velocity_to_si(&self, v: f64) -> f64 {
+        v * self.length / self.time
+    }
+
+    pub fn velocity_from_si(&self, v: f64) -> f64 {
+        v * self.time / self.length
+    }
+
+    pub fn force_to_si(&self, f: f64) -> f64 {
+        f * self.mass * self.length / self.time.powi(2)
+    }
+
+    pub fn force_from_si(&self, f: f64) -> f64 {
+        f * self.time.powi(2) / (self.mass * self.length)
+    }
+
+    /// Convert a density given in kg/m³ into mass units per cubic length unit.
+    pub fn density_from_si(&self, d: f64) -> f64 {
+        d * self.length.powi(3) / self.mass
+    }
+
+    pub fn density_to_si(&self, d: f64) -> f64 {
+        d * self.mass / self.length.powi(3)
+    }
+
+    /// Convert a value in this unit system into another one, given the exponents of its length,
+    /// mass and time dimensions, e.g. `(1, 0, -1)` for a velocity.
+    pub fn convert(&self, value: f64, dims: (i32, i32, i32), to: &UnitSystem) -> f64 {
+        let (l, m, t) = dims;
+        value
+            * (self.length / to.length).powi(l)
+            * (self.mass / to.mass).powi(m)
+            * (self.time / to.time).powi(t)
+    }
+}
+
+impl std::default::Default for UnitSystem {
+    fn default() -> Self {
+        Self::canvas()
+    }
+}
+
+impl std::fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{:>13}", "UNITS")?;
+        writeln!(f, "{:>12}: {} m", "Length", self.length)?;
+        writeln!(f, "{:>12}: {} kg", "Mass", self.mass)?;
+        writeln!(f, "{:>12}: {} s", "Time", self.time)?;
+        writeln!(f, "{:>12}: {}", "G", self.g())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::close;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn presets() {
+        assert_eq!(UnitSystem::si().g(), G_SI);
+        assert!(close(UnitSystem::astronomical().g(), 4.0 * PI * PI, 1e-3));
+        assert!(close(UnitSystem::nbody(AU, SOLAR_MASS).g(), 1.0, 1e-12));
+
+        // A tick of 100 s squares into the factor of 10⁴ we used to scale forces by.
+        assert!(close(UnitSystem::canvas().g(), G_SI * 10_000.0, 1e-12));
+    }
+
+    #[test]
+    fn conversions() {
+        let si = UnitSystem::si();
+        let au = UnitSystem::astronomical();
+
+        assert_eq!(au.length_to_si(1.0), AU);
+        assert_eq!(au.length_from_si(AU), 1.0);
+        assert!(close(au.velocity_to_si(2.0 * PI), 29_785.0, 1e-3));
+        assert!(close(au.convert(2.0 * PI, (1, 0, -1), &si), 29_785.0, 1e-3));
+        assert!(close(si.convert(AU, (1, 0, 0), &au), 1.0, 1e-12));
+
+        let d = au.density_from_si(5513.0);
+        assert!(close(au.density_to_si(d), 5513.0, 1e-12));
+        let f = au.force_from_si(1.0);
+        assert!(close(au.force_to_si(f), 1.0, 1e-12));
+    }
+}
@@ -1,35 +1,108 @@
 extern crate rand;
 
+use rand::Rng;
 use std::cell::RefCell;
-use std::f64::consts::PI;
+use std::collections::HashMap;
+use uuid::Uuid;
 
 use super::*;
 
 type Canvas = web_sys::CanvasRenderingContext2d;
 
 pub struct Universe {
+    /// Width and height of the universe in world length units.
     dimensions: (f64, f64),
     planets: Vec<Rc<RefCell<Planet>>>,
+    /// Massless particles pulled along by the planets.
+    particles: Tracers,
+    particle_config: ParticleConfig,
+    /// The units all positions, masses and times are expressed in.
+    units: UnitSystem,
+    /// Time passing on each tick, in time units of `units`.
+    dt: f64,
+    /// Time elapsed since the universe began, in time units.
+    time: f64,
+    /// Events that happened since they were last drained.
+    events: Vec<Event>,
+    camera: Camera,
+    /// Start and current end of a planet launch in progress.
+    launch_preview: Option<(Point, Point)>,
+    trails: TrailConfig,
+    style: StyleConfig,
+    /// Barnes-Hut threshold of the quad tree, see [`QuadConfig`](../quad/struct.QuadConfig.html).
+    theta: f64,
+    /// Plummer softening length of gravity, see [`QuadConfig`](../quad/struct.QuadConfig.html).
+    softening: f64,
+    /// Whether the quad tree is drawn on top of the universe.
+    show_quad_tree: bool,
+    /// Arrows drawn on top of the planets.
+    overlays: OverlayConfig,
+    /// How the gravitational field is drawn behind the planets.
+    field: FieldConfig,
+    /// A dark matter halo pulling on everything, if there is one.
+    halo: Option<Halo>,
+    /// Id of the currently selected planet.
+    selected: Option<Uuid>,
+    boundary: Boundary,
+    collisions: CollisionModel,
+    solver: Solver,
+    integrator: Integrator,
+    /// The pulls the last tick ended with, for the next one to start with.
+    pulls: Option<Pulls>,
 }
 
 impl Universe {
+    /// Create a `Universe` the size of the canvas, measured in
+    /// [`UnitSystem::canvas`](../units/struct.UnitSystem.html#method.canvas) units.
     pub fn new(dimensions: (f64, f64)) -> Self {
+        Self::with_units(dimensions, UnitSystem::canvas(), 1.0, dimensions)
+    }
+
+    /// Create a `Universe` of the given world `dimensions`, stepping `dt` time units per tick and
+    /// drawn onto a canvas of `viewport` pixels.
+    pub fn with_units(
+        dimensions: (f64, f64),
+        units: UnitSystem,
+        dt: f64,
+        viewport: (f64, f64),
+    ) -> Self {
         Self {
             dimensions,
             planets: Vec::new(),
+            particles: Tracers::new(),
+            particle_config: ParticleConfig::default(),
+            units,
+            dt,
+            time: 0.0,
+            events: Vec::new(),
+            camera: Camera::fit(dimensions, viewport),
+            launch_preview: None,
+            trails: TrailConfig::default(),
+            style: StyleConfig::default(),
+            theta: 0.5,
+            softening: 0.0,
+            show_quad_tree: false,
+            overlays: OverlayConfig::default(),
+            field: FieldConfig::default(),
+            halo: None,
+            selected: None,
+            boundary: Boundary::Wrap,
+            collisions: CollisionModel::default(),
+            solver: Solver::default(),
+            integrator: Integrator::default(),
+            pulls: None,
         }
     }
 
     pub fn init_random(&mut self) {
         // let mut rng = rand::thread_rng();
-        self.planets.push(Rc::new(RefCell::new(Planet::new_sun(
+        self.add(Planet::new_sun(
             self.dimensions.0 / 2.0,
             self.dimensions.1 / 2.0,
-        ))));
+        ));
 
         for _i in 0..NO_OF_PLANETS {
-            let planet = Rc::new(RefCell::new(Planet::new_rng(self.dimensions)));
-            self.planets.push(planet);
+            self.add(Planet::new_rng(self.dimensions));
         }
     }
 
@@ -38,102 +111,1158 @@ impl Universe {
         web_sys::console::log_1(val);
     }
 
+    pub fn units(&self) -> UnitSystem {
+        self.units
+    }
+
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    pub fn set_dt(&mut self, dt: f64) {
+        self.dt = dt;
+    }
+
+    /// Time elapsed since the universe began, in time units.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn dimensions(&self) -> (f64, f64) {
+        self.dimensions
+    }
+
+    /// Whether a point lies within the dimensions of the universe.
+    pub fn contains(&self, pos: Point) -> bool {
+        (0.0..=self.dimensions.0).contains(&pos.x) && (0.0..=self.dimensions.1).contains(&pos.y)
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    pub fn collisions(&self) -> CollisionModel {
+        self.collisions
+    }
+
+    pub fn set_collisions(&mut self, collisions: CollisionModel) {
+        self.collisions = collisions;
+    }
+
+    pub fn solver(&self) -> Solver {
+        self.solver
+    }
+
+    pub fn set_solver(&mut self, solver: Solver) {
+        self.solver = solver;
+        self.pulls = None;
+    }
+
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    pub fn trail_config(&self) -> TrailConfig {
+        self.trails
+    }
+
+    pub fn set_trail_config(&mut self, trails: TrailConfig) {
+        self.trails = trails;
+    }
+
+    pub fn style(&self) -> StyleConfig {
+        self.style
+    }
+
+    pub fn set_style(&mut self, style: StyleConfig) {
+        self.style = style;
+    }
+
+    pub fn theta(&self) -> f64 {
+        self.theta
+    }
+
+    pub fn set_theta(&mut self, theta: f64) {
+        self.theta = theta;
+        self.pulls = None;
+    }
+
+    pub fn softening(&self) -> f64 {
+        self.softening
+    }
+
+    pub fn set_softening(&mut self, softening: f64) {
+        self.softening = softening;
+        self.pulls = None;
+    }
+
+    pub fn show_quad_tree(&self) -> bool {
+        self.show_quad_tree
+    }
+
+    pub fn set_show_quad_tree(&mut self, show: bool) {
+        self.show_quad_tree = show;
+    }
+
+    pub fn overlays(&self) -> OverlayConfig {
+        self.overlays
+    }
+
+    pub fn set_overlays(&mut self, overlays: OverlayConfig) {
+        self.overlays = overlays;
+    }
+
+    pub fn field(&self) -> FieldConfig {
+        self.field
+    }
+
+    pub fn set_field(&mut self, field: FieldConfig) {
+        self.field = field;
+    }
+
+    /// Sample the gravitational field of all living planets across the viewport, through the
+    /// quad tree.
+    pub fn sample_field(&self, cfg: &FieldConfig) -> FieldGrid {
+        FieldGrid::sample(&self.build_quad_tree(), &self.camera, self.units.g(), cfg)
+    }
+
+    pub fn halo(&self) -> Option<Halo> {
+        self.halo
+    }
+
+    pub fn set_halo(&mut self, halo: Option<Halo>) {
+        self.halo = halo;
+        self.pulls = None;
+    }
+
+    pub fn selected(&self) -> Option<Rc<RefCell<Planet>>> {
+        let id = self.selected?;
+        self.planets.iter().find(|p| p.borrow().id() == id).cloned()
+    }
+
+    pub fn select(&mut self, id: Option<Uuid>) {
+        self.selected = id;
+    }
+
+    /// Fit the whole universe into the viewport again.
+    pub fn reset_camera(&mut self) {
+        self.camera = Camera::fit(self.dimensions, self.camera.viewport());
+    }
+
+    /// Center the camera on the planet it follows, if that's still around.
+    pub fn update_camera(&mut self) {
+        if let Some(id) = self.camera.follow() {
+            match self.planets.iter().find(|p| p.borrow().id() == id) {
+                Some(p) => self.camera.set_center(p.borrow().pos()),
+                None => self.camera.set_follow(None),
+            }
+        }
+    }
+
+    /// Find the `Planet` covering the given world coordinates. Planets too small to hit are
+    /// picked if they're within `tolerance` length units. Where planets overlap, the one whose
+    /// edge is furthest away wins, like in
+    /// [`QuadNode::pick`](../quad/struct.QuadNode.html#method.pick).
+    pub fn planet_at(&self, pos: Point, tolerance: f64) -> Option<Rc<RefCell<Planet>>> {
+        // A single pick isn't worth building a quad tree for.
+        let gap = |p: &Rc<RefCell<Planet>>| {
+            let p = p.borrow();
+            p.pos().distance_to(pos) - p.radius().max(tolerance)
+        };
+        self.planets
+            .iter()
+            .filter(|p| !p.borrow().dead() && gap(p) <= 0.0)
+            .min_by(|a, b| gap(a).total_cmp(&gap(b)))
+            .cloned()
+    }
+
+    pub fn planets(&self) -> &[Rc<RefCell<Planet>>] {
+        &self.planets
+    }
+
+    /// Add a fully specified `Planet` and return its id.
+    pub fn add(&mut self, planet: Planet) -> Uuid {
+        let id = planet.id();
+        self.events.push(Event::Spawn {
+            id,
+            pos: planet.pos(),
+            mass: planet.mass(),
+            time: self.time,
+        });
+        self.planets.push(Rc::new(RefCell::new(planet)));
+        id
+    }
+
+    /// Add a `Planet` at the given world coordinates.
     pub fn add_planet(&mut self, x: f64, y: f64) {
-        let p = Rc::new(RefCell::new(Planet::new_semi_rng(x, y)));
-        self.planets.push(p);
+        self.add(Planet::new_semi_rng(x, y));
     }
 
-    pub fn tick_n_draw<'a>(&self, ctx: &'a Canvas, time: f64) -> &'a Canvas {
-        let mut qtree = self.init_quad_tree();
-        for planet in &self.planets {
-            qtree.insert(planet.clone()).unwrap();
+    /// Spawn a `Planet` at the given world coordinates, with its mass, density and velocity set
+    /// according to `cfg`. Unless spawned at random, it's put on an orbit around the
+    /// [`dominant_attractor`](#method.dominant_attractor).
+    pub fn spawn_planet(&mut self, pos: Point, cfg: &SpawnConfig) {
+        let attractor = match cfg.mode {
+            SpawnMode::Random => None,
+            _ => self.dominant_attractor(pos),
+        };
+
+        let velocity = match attractor {
+            Some(attractor) => {
+                let attractor = attractor.borrow();
+                let mu = self.units.g() * (attractor.mass() + cfg.mass);
+                let e = match cfg.mode {
+                    SpawnMode::Eccentric => cfg.eccentricity,
+                    _ => 0.0,
+                };
+                Newtonian::velocity(&*attractor)
+                    + orbit::orbit_velocity(pos - attractor.pos(), mu, e, cfg.retrograde)
+            }
+            None => {
+                let mut rng = rand::thread_rng();
+                Point::new(rng.gen_range(-0.05, 0.05), rng.gen_range(-0.05, 0.05))
+            }
+        };
+
+        self.add_planet_with_velocity(pos, velocity, cfg);
+    }
+
+    /// Add a `Planet` at the given world coordinates, moving at `velocity` and with mass and
+    /// density taken from `cfg`.
+    pub fn add_planet_with_velocity(&mut self, pos: Point, velocity: Point, cfg: &SpawnConfig) {
+        self.add(Planet::new(
+            pos.x,
+            pos.y,
+            cfg.density,
+            cfg.radius(),
+            velocity,
+        ));
+    }
+
+    /// Velocity for a planet launched by pulling it back from `start` to `end`, slingshot-style.
+    /// It will cover the distance pulled in [`LAUNCH_TICKS`](../constant.LAUNCH_TICKS.html) ticks.
+    pub fn launch_velocity(&self, start: Point, end: Point) -> Point {
+        (start - end) / (LAUNCH_TICKS * self.dt)
+    }
+
+    pub fn particles(&self) -> &Tracers {
+        &self.particles
+    }
+
+    pub fn particle_config(&self) -> ParticleConfig {
+        self.particle_config
+    }
+
+    pub fn set_particle_config(&mut self, cfg: ParticleConfig) {
+        self.particle_config = cfg;
+    }
+
+    /// Release a test particle at the given world coordinates, moving at `velocity`.
+    pub fn add_particle(&mut self, pos: Point, velocity: Point) {
+        self.particles.push(pos, velocity);
+    }
+
+    /// Release a test particle at the given world coordinates on a circular orbit around the
+    /// [`dominant_attractor`](#method.dominant_attractor), or at rest if there's none.
+    pub fn spawn_particle(&mut self, pos: Point) {
+        let velocity = self.circular_velocity(pos);
+        self.add_particle(pos, velocity);
+    }
+
+    /// Release test particles on a grid `spacing` length units apart across `area`. They're put
+    /// on circular orbits around the dominant attractor where they start if `orbiting`, or at
+    /// rest otherwise. Spots covered by planets are left out. Returns the number of particles
+    /// released.
+    pub fn seed_particles(&mut self, area: &Rect, spacing: f64, orbiting: bool) -> usize {
+        let qtree = self.build_quad_tree();
+        let points: Vec<Point> = particles::grid(area, spacing)
+            .into_iter()
+            .filter(|p| qtree.pick(*p, 0.0).is_none())
+            .collect();
+
+        for pos in &points {
+            let velocity = if orbiting {
+                self.circular_velocity(*pos)
+            } else {
+                Point::default()
+            };
+            self.add_particle(*pos, velocity);
         }
+        points.len()
+    }
 
-        let mut ctx = self.refresh_canvas(ctx);
-        for planet in &self.planets {
-            qtree.update_body(planet.clone(), time).unwrap();
-            ctx = self.draw_planet(ctx, planet.clone());
+    pub fn clear_particles(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Velocity of a circular orbit at `pos` around the dominant attractor, for a body too light
+    /// to pull back.
+    fn circular_velocity(&self, pos: Point) -> Point {
+        match self.dominant_attractor(pos) {
+            Some(attractor) => {
+                let attractor = attractor.borrow();
+                let mu = self.units.g() * attractor.mass();
+                Newtonian::velocity(&*attractor)
+                    + orbit::circular_velocity(pos - attractor.pos(), mu, false)
+            }
+            None => Point::default(),
         }
+    }
 
-        ctx
+    /// Set the rubber band drawn between where a launch started and where it's being pulled to,
+    /// in world coordinates.
+    pub fn set_launch_preview(&mut self, preview: Option<(Point, Point)>) {
+        self.launch_preview = preview;
+    }
+
+    /// Remove the `Planet` with the given id, dropping it from the selection and camera too.
+    /// Returns whether there was such a planet.
+    pub fn remove(&mut self, id: Uuid) -> bool {
+        let count = self.planets.len();
+        self.planets.retain(|p| p.borrow().id() != id);
+        if self.selected == Some(id) {
+            self.selected = None;
+        }
+        self.update_camera();
+
+        let removed = self.planets.len() < count;
+        if removed {
+            self.events.push(Event::Remove {
+                id,
+                time: self.time,
+            });
+        }
+        removed
+    }
+
+    /// Take all events that happened since the last call. Events pile up until they're drained.
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, Event> {
+        self.events.drain(..)
+    }
+
+    /// Capture the complete state of the universe, along with the settings it runs by.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            dimensions: self.dimensions,
+            units: self.units,
+            dt: self.dt,
+            time: self.time,
+            solver: self.solver,
+            integrator: self.integrator,
+            boundary: self.boundary,
+            theta: self.theta,
+            softening: self.softening,
+            collisions: self.collisions,
+            halo: self.halo,
+            planets: self
+                .planets
+                .iter()
+                .filter(|p| !p.borrow().dead())
+                .map(|p| p.borrow().state())
+                .collect(),
+            tracers: self
+                .particles
+                .positions()
+                .iter()
+                .zip(self.particles.velocities())
+                .map(|(pos, velocity)| TracerState {
+                    pos: *pos,
+                    velocity: *velocity,
+                })
+                .collect(),
+        }
+    }
+
+    /// Replace the state of the universe with a snapshot. The viewport and the settings for how
+    /// the universe is drawn are kept, the camera is fit to the restored universe.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.dimensions = snapshot.dimensions;
+        self.units = snapshot.units;
+        self.dt = snapshot.dt;
+        self.time = snapshot.time;
+        self.solver = snapshot.solver;
+        self.integrator = snapshot.integrator;
+        self.boundary = snapshot.boundary;
+        self.theta = snapshot.theta;
+        self.softening = snapshot.softening;
+        self.collisions = snapshot.collisions;
+        self.halo = snapshot.halo;
+        self.planets = snapshot
+            .planets
+            .iter()
+            .map(|state| Rc::new(RefCell::new(Planet::from_state(state))))
+            .collect();
+        self.particles.clear();
+        for tracer in &snapshot.tracers {
+            self.particles.push(tracer.pos, tracer.velocity);
+        }
+        self.selected = None;
+        self.launch_preview = None;
+        self.pulls = None;
+        self.reset_camera();
+    }
+
+    /// Take over how `other` is simulated and drawn, but none of its bodies. The time step only
+    /// carries over between universes measured in the same units, and the halo belongs to the
+    /// scenario so it's left as it is.
+    pub fn carry_settings(&mut self, other: &Universe) {
+        if self.units == other.units {
+            self.dt = other.dt;
+        }
+        self.solver = other.solver;
+        self.integrator = other.integrator;
+        self.boundary = other.boundary;
+        self.theta = other.theta;
+        self.softening = other.softening;
+        self.collisions = other.collisions;
+        self.particle_config = other.particle_config;
+        self.trails = other.trails;
+        self.style = other.style;
+        self.show_quad_tree = other.show_quad_tree;
+        self.overlays = other.overlays;
+        self.field = other.field;
+        self.pulls = None;
+    }
+
+    /// Show the bodies of a recorded [`TrajectoryFrame`](../trajectory/struct.TrajectoryFrame.html)
+    /// in place of the current planets. Planets that were already there carry on their trails.
+    pub fn replay(&mut self, frame: &TrajectoryFrame) {
+        let previous: HashMap<Uuid, Rc<RefCell<Planet>>> = self
+            .planets
+            .drain(..)
+            .map(|p| (p.borrow().id(), p.clone()))
+            .collect();
+
+        self.time = frame.time;
+        for state in &frame.bodies {
+            let planet = Planet::from_state(state);
+            if let Some(old) = previous.get(&state.id) {
+                planet.carry_on(&old.borrow());
+            }
+            planet.record_trail(self.trails.length);
+            self.planets.push(Rc::new(RefCell::new(planet)));
+        }
+        self.update_camera();
+    }
+
+    /// The first sun of the universe, if it has one.
+    pub fn sun(&self) -> Option<Rc<RefCell<Planet>>> {
+        self.planets
+            .iter()
+            .find(|p| p.borrow().is_sun() && !p.borrow().dead())
+            .cloned()
+    }
+
+    /// Net gravitational force all other planets exert on `planet`, softened like the solvers'.
+    #[allow(non_snake_case)]
+    pub fn net_force(&self, planet: &Planet) -> Point {
+        let G = self.units.g();
+        self.planets
+            .iter()
+            .map(|other| other.borrow())
+            .filter(|other| !other.dead() && other.id() != planet.id())
+            .fold(Point::default(), |acc, other| {
+                let direction = other.pos() - planet.pos();
+                let d = (direction.mag().powi(2) + self.softening.powi(2)).sqrt();
+                if d == 0.0 {
+                    acc
+                } else {
+                    acc + direction * (G * planet.mass() * other.mass() / (d * d * d))
+                }
+            })
+    }
+
+    /// Collect the live properties of `planet`, including its orbit around the
+    /// [`sun`](#method.sun).
+    pub fn inspect(&self, planet: &Planet) -> Inspection {
+        let orbit = self
+            .sun()
+            .filter(|sun| sun.borrow().id() != planet.id())
+            .map(|sun| {
+                let sun = sun.borrow();
+                let mu = self.units.g() * (planet.mass() + sun.mass());
+                (planet.orbit_around(&sun, self.units.g()), mu)
+            });
+
+        Inspection {
+            id: planet.id(),
+            pos: planet.pos(),
+            velocity: Newtonian::velocity(planet),
+            mass: planet.mass(),
+            radius: planet.radius(),
+            density: planet.density(),
+            net_force: self.net_force(planet),
+            pinned: planet.pinned(),
+            orbit,
+            units: self.units,
+        }
+    }
+
+    /// Find the `Planet` exerting the strongest gravitational pull at `pos`.
+    pub fn dominant_attractor(&self, pos: Point) -> Option<Rc<RefCell<Planet>>> {
+        let pull = |p: &Rc<RefCell<Planet>>| {
+            let p = p.borrow();
+            let d = p.pos().distance_to(pos);
+            p.mass() / (d * d)
+        };
+
+        self.planets
+            .iter()
+            .filter(|p| !p.borrow().dead() && p.borrow().pos() != pos)
+            .max_by(|a, b| pull(a).total_cmp(&pull(b)))
+            .cloned()
     }
 
-    /// The main computation of the universe. In a nested loop, we look at each planet,
-    /// calculate the gravitational force in relation to each other planet, then sum up the
-    /// forces, scale them to make them more visible and set the net acceleration.
+    /// Advance the universe by one tick and draw it onto the canvas.
+    pub fn tick_n_draw<'a>(&mut self, ctx: &'a Canvas, _time: f64) -> &'a Canvas {
+        self.tick();
+        self.draw(ctx)
+    }
+
+    /// The main computation of the universe. We work out the gravitational pull on each planet
+    /// with the chosen [`Solver`](../solver/enum.Solver.html) and move the planets along with the
+    /// chosen [`Integrator`](../solver/enum.Integrator.html). Positions are only updated once
+    /// every planet has been accelerated.
+    /// The pulls are worked out once per tick, at its end, and kept for the next tick to start
+    /// with, unless anything was moved, added or removed in between.
+    /// Planets touching each other after they moved collide according to the
+    /// [`CollisionModel`](../collision/enum.CollisionModel.html).
     /// Since we're only holding references to our planets, when one gets eaten, we initially set
     /// it to `dead` and remove it from the `planets` vector after the loop is finished.
-    #[allow(non_snake_case)]
-    pub fn tick_n_draw_brute<'a>(&mut self, ctx: &'a Canvas, _time: f64) -> &'a Canvas {
-        let G = 6.67 * 10_f64.powf(-11.0);
+    /// Test particles are moved along with the planets, but never collide. They're only absorbed
+    /// by the planets they touch, if [`absorb`](../particles/struct.ParticleConfig.html#structfield.absorb)
+    /// is set.
+    pub fn tick(&mut self) {
+        let dt = self.dt;
+        let mut events = vec![];
+
+        let kick = match self.integrator {
+            Integrator::Euler => dt,
+            Integrator::Leapfrog => dt / 2.0,
+        };
+        let pulls = match self.pulls.take() {
+            Some(pulls) if pulls.hold_for(self) => pulls,
+            _ => self.pull(self.pull_tree().as_ref()),
+        };
+        self.kick(&pulls, kick);
 
-        for (i, p) in self.planets.iter().enumerate() {
+        // Only move planets once all velocities are updated, so every planet is pulled towards
+        // where the others are at the start of the tick.
+        let mut previous = Vec::with_capacity(self.planets.len());
+        for p in self.planets.iter() {
             let p = p.borrow();
-            if p.dead() {
-                continue;
+            previous.push(p.pos());
+            if !p.pinned() && !p.dead() {
+                match self.boundary {
+                    Boundary::Wrap => p.advance(dt, self.dimensions),
+                    Boundary::Open => {
+                        p.drift(dt);
+                        if !self.contains(p.pos()) {
+                            p.die();
+                            events.push(Event::Escape {
+                                id: p.id(),
+                                pos: p.pos(),
+                                time: self.time + dt,
+                            });
+                        }
+                    }
+                }
             }
+            p.grow_older(dt);
+            p.record_trail(self.trails.length);
+        }
+        self.drift_particles(dt);
+        self.time += dt;
+        self.events.extend(events);
+        self.collide(&previous);
 
-            let mut forces: Vec<Point> = vec![];
-            for (j, other_p) in self.planets.iter().enumerate() {
-                let other_p = other_p.borrow();
-                if p.dead() {
-                    continue;
-                };
+        let planets = self
+            .planets
+            .iter()
+            .filter(|p| !p.borrow_mut().dead())
+            .cloned()
+            .collect();
+
+        self.planets = planets;
+        // One quad tree serves absorbing test particles as well as the pulls.
+        let qtree = self.pull_tree();
+        if self.particle_config.absorb {
+            if let Some(qtree) = &qtree {
+                self.absorb_particles(qtree);
+            }
+        }
+        let pulls = self.pull(qtree.as_ref());
+        if self.integrator == Integrator::Leapfrog {
+            self.kick(&pulls, dt / 2.0);
+        }
+        self.pulls = Some(pulls);
+        self.particles
+            .record_trails(self.particle_config.streamlines);
+        self.update_camera();
+    }
+
+    /// The quad tree the pulls are worked out with, if the solver or the test particles need
+    /// one.
+    fn pull_tree(&self) -> Option<QuadNode> {
+        if self.solver == Solver::BarnesHut || !self.particles.is_empty() {
+            Some(self.build_quad_tree())
+        } else {
+            None
+        }
+    }
+
+    /// Work out the pull on every planet and test particle where they are now, with the quad
+    /// tree of [`pull_tree`](#method.pull_tree).
+    fn pull(&self, qtree: Option<&QuadNode>) -> Pulls {
+        Pulls {
+            bodies: Pulls::bodies(&self.planets),
+            positions: self.particles.positions().to_vec(),
+            planets: self.accelerations_with(qtree),
+            particles: match qtree {
+                Some(qtree) if !self.particles.is_empty() => {
+                    self.particle_accelerations_with(qtree)
+                }
+                _ => vec![],
+            },
+        }
+    }
+
+    /// Kick every planet and test particle over `dt` by its pull.
+    fn kick(&mut self, pulls: &Pulls, dt: f64) {
+        self.accelerate(&pulls.planets, dt);
+        self.particles.accelerate(&pulls.particles, dt);
+    }
 
-                if i != j {
-                    let direction = other_p.pos() - p.pos();
-                    let d = direction.mag();
-                    let F = (G * p.mass() * other_p.mass()) / (d * d);
-
-                    if direction.mag() <= p.radius() && F > EATING_FORCE {
-                        //self.log(&F.into());
-                        p.eat(&other_p);
-                        other_p.die();
-                        continue;
-                    } else {
-                        // The gravitational force between two bodies will always be the same for
-                        // both. Note that although I am applying the same gravitational force to
-                        // Earth as it is to me, the acceleration happening is a very one-sided
-                        // affair. That's because Earth probably ate a few more planets than I did
-                        // and can throw all her weight in the ring, or, in Newton's words:
-                        // F = a/m
-                        let acc = direction.norm() * (F / p.mass());
-                        forces.push(acc);
+    /// Move every test particle along its velocity, wrapping them around or letting them escape
+    /// at the edges like planets.
+    fn drift_particles(&mut self, dt: f64) {
+        let mut particles = std::mem::take(&mut self.particles);
+        particles.drift(dt);
+        match self.boundary {
+            Boundary::Wrap => particles.wrap(self.dimensions),
+            Boundary::Open => particles.retain(|p| self.contains(p)),
+        }
+        self.particles = particles;
+    }
+
+    /// Remove the test particles that ended up inside a planet of `qtree`.
+    fn absorb_particles(&mut self, qtree: &QuadNode) {
+        self.particles.retain(|p| qtree.pick(p, 0.0).is_none());
+    }
+
+    /// Kick every planet by its acceleration, in the order of `planets`, over `dt`, and keep the
+    /// acceleration to be shown.
+    /// Pinned planets, like our sun, stay where they are.
+    fn accelerate(&self, accelerations: &[Point], dt: f64) {
+        for (p, &acc) in self.planets.iter().zip(accelerations) {
+            let p = p.borrow();
+            p.set_acceleration(acc);
+            if !p.pinned() && !p.dead() {
+                p.accelerate(acc * dt);
+            }
+        }
+    }
+
+    /// The gravitational acceleration of each planet, in the order of `planets`, as worked out
+    /// by the [`Solver`](../solver/enum.Solver.html), plus the pull of the [`halo`](#method.halo).
+    /// Dead planets neither pull nor get pulled.
+    pub fn accelerations(&self) -> Vec<Point> {
+        self.accelerations_with(None)
+    }
+
+    /// The [`accelerations`](#method.accelerations), with the Barnes-Hut solver walking `qtree`
+    /// if it's given instead of building its own.
+    #[allow(non_snake_case)]
+    fn accelerations_with(&self, qtree: Option<&QuadNode>) -> Vec<Point> {
+        let G = self.units.g();
+        let mut accelerations: Vec<Point> = match self.solver {
+            Solver::Direct => self
+                .planets
+                .iter()
+                .map(|p| {
+                    let p = p.borrow();
+                    if p.dead() {
+                        return Point::default();
+                    }
+                    // The gravitational force between two bodies will always be the same for
+                    // both. Note that although I am applying the same gravitational force to
+                    // Earth as it is to me, the acceleration happening is a very one-sided
+                    // affair. That's because Earth probably ate a few more planets than I did
+                    // and can throw all her weight in the ring, or, in Newton's words:
+                    // a = F/m
+                    self.net_force(&p) * (1.0 / p.mass())
+                })
+                .collect(),
+            Solver::BarnesHut => {
+                let built;
+                let qtree = match qtree {
+                    Some(qtree) => qtree,
+                    None => {
+                        built = self.build_quad_tree();
+                        &built
                     }
+                };
+                self.planets
+                    .iter()
+                    .map(|p| {
+                        let p = p.borrow();
+                        if p.dead() {
+                            Point::default()
+                        } else {
+                            qtree.acceleration(p.pos(), p.id(), G)
+                        }
+                    })
+                    .collect()
+            }
+        };
+
+        if let Some(halo) = self.halo {
+            for (p, acc) in self.planets.iter().zip(accelerations.iter_mut()) {
+                let p = p.borrow();
+                if !p.dead() {
+                    *acc += halo.acceleration(p.pos());
                 }
             }
+        }
+        accelerations
+    }
 
-            // We start with a force of (0, 0) and apply each previously calculated gravitational
-            // force in turn.
-            let mut net_force = Point::default();
-            net_force = forces.into_iter().fold(net_force, |acc, curr| acc + curr);
+    /// The gravitational acceleration of each test particle, in the order of
+    /// [`particles`](#method.particles). The quad tree is built from the massive planets alone and
+    /// evaluated at every particle, whatever the solver: particles don't need the exact pull of
+    /// every planet, and there are far too many of them to sum it up. The halo pulls on top.
+    pub fn particle_accelerations(&self) -> Vec<Point> {
+        self.particle_accelerations_with(&self.build_quad_tree())
+    }
 
-            // We need to scale F for now to have something actually happening on the screen.
-            p.accelerate(net_force * SCALE_F);
+    /// The [`particle_accelerations`](#method.particle_accelerations) in the given `qtree` of
+    /// the planets.
+    fn particle_accelerations_with(&self, qtree: &QuadNode) -> Vec<Point> {
+        let positions = self.particles.positions();
+        let mut accelerations = qtree.accelerations(positions, self.units.g());
+        if let Some(halo) = self.halo {
+            for (acc, pos) in accelerations.iter_mut().zip(positions) {
+                *acc += halo.acceleration(*pos);
+            }
+        }
+        accelerations
+    }
+
+    /// Kinetic and potential energy of the universe, in its own units. Pinned planets don't
+    /// move, so only add to the potential energy. The potential energy includes that of every
+    /// planet in the halo, if there is one.
+    #[allow(non_snake_case)]
+    pub fn energy(&self) -> (f64, f64) {
+        let G = self.units.g();
+        let planets: Vec<_> = self
+            .planets
+            .iter()
+            .map(|p| p.borrow())
+            .filter(|p| !p.dead())
+            .collect();
 
-            // Let's have Sun stay in the middle of the universe.
-            if i > 0 {
-                p.update(self.dimensions);
+        let mut kinetic = 0.0;
+        let mut potential = 0.0;
+        for (i, p) in planets.iter().enumerate() {
+            if !p.pinned() {
+                let v = Newtonian::velocity(&**p).mag();
+                kinetic += 0.5 * p.mass() * v * v;
+            }
+            for other in &planets[i + 1..] {
+                let d = p.pos().distance_to(other.pos());
+                let d = (d * d + self.softening.powi(2)).sqrt();
+                // Planets on top of each other are about to merge, so don't pull each other.
+                if d > 0.0 {
+                    potential -= G * p.mass() * other.mass() / d;
+                }
+            }
+            if let Some(halo) = self.halo {
+                potential += p.mass() * halo.potential(p.pos());
             }
         }
+        (kinetic, potential)
+    }
 
-        let planets = self
+    /// Resolve the collisions of all pairs of planets that touch, or touched somewhere along the
+    /// way from their `previous` positions. Those that met along the way are put back to where
+    /// they met first. Debris flying off is added to the universe.
+    ///
+    /// Candidates for a collision are looked up in a quad tree, within reach of each planet's
+    /// path. That's the sum of its radius and the largest radius and distance travelled by any
+    /// planet.
+    fn collide(&mut self, previous: &[Point]) {
+        let g = self.units.g();
+        let (half_width, half_height) = (self.dimensions.0 / 2.0, self.dimensions.1 / 2.0);
+
+        // Planets that wrapped around the edges are only looked at where they are now.
+        let paths: Vec<(Point, Point)> = self
             .planets
             .iter()
-            .filter(|p| !p.borrow_mut().dead())
-            .cloned()
+            .zip(previous)
+            .map(|(p, from)| {
+                let to = p.borrow().pos();
+                let jump = to - *from;
+                if jump.x.abs() > half_width || jump.y.abs() > half_height {
+                    (to, to)
+                } else {
+                    (*from, to)
+                }
+            })
             .collect();
 
-        self.planets = planets;
+        let index: HashMap<Uuid, usize> = self
+            .planets
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.borrow().id(), i))
+            .collect();
+        let max_radius = self
+            .planets
+            .iter()
+            .map(|p| p.borrow().radius())
+            .fold(0.0, f64::max);
+        let max_travel = paths
+            .iter()
+            .map(|(from, to)| from.distance_to(*to))
+            .fold(0.0, f64::max);
 
-        let mut ctx = self.refresh_canvas(ctx);
-        for p in self.planets.iter() {
-            ctx = self.draw_planet(ctx, p.clone());
+        let qtree = self.build_quad_tree();
+        let mut debris = vec![];
+
+        for (i, a) in self.planets.iter().enumerate() {
+            let (a_from, a_to) = paths[i];
+            let reach = a.borrow().radius() + max_radius + max_travel;
+            let area = Rect::around(a_from, a_to).expand(reach);
+            let mut candidates: Vec<usize> = qtree
+                .query(&area)
+                .iter()
+                .filter_map(|b| index.get(&b.borrow().id()).copied())
+                .filter(|j| *j > i)
+                .collect();
+            candidates.sort_unstable();
+
+            for j in candidates {
+                let (a, b) = (a.borrow(), self.planets[j].borrow());
+                if a.dead() || b.dead() {
+                    continue;
+                }
+
+                if !collision::overlap(&a, &b) {
+                    let (b_from, b_to) = paths[j];
+                    let distance = a.radius() + b.radius();
+                    match collision::time_of_impact(a_from, a_to, b_from, b_to, distance) {
+                        // Planets already in contact at the start stay where they are.
+                        Some(0.0) => {}
+                        Some(t) => {
+                            a.set_pos(a_from + (a_to - a_from) * t);
+                            b.set_pos(b_from + (b_to - b_from) * t);
+                        }
+                        None => continue,
+                    }
+                }
+
+                if let Outcome::Merged {
+                    eater,
+                    eaten,
+                    mass,
+                    eaten_mass,
+                    pos,
+                    debris: pieces,
+                } = collision::resolve(self.collisions, &a, &b, g)
+                {
+                    self.events.push(Event::Merge {
+                        id: eater,
+                        eaten,
+                        mass,
+                        eaten_mass,
+                        pos,
+                        time: self.time,
+                    });
+                    debris.extend(pieces);
+                }
+            }
+        }
+
+        for piece in debris {
+            self.add(piece);
         }
+    }
 
+    /// Draw the universe as seen by the camera into an [`Svg`](../render/struct.Svg.html)
+    /// document, with trails, the quad tree and velocities as they're currently shown.
+    pub fn to_svg(&self) -> String {
+        let (width, height) = self.camera.viewport();
+        let mut svg = Svg::new(width, height);
+        self.render(&mut svg);
+        svg.to_string()
+    }
+
+    /// Draw the universe onto the canvas.
+    pub fn draw<'a>(&self, ctx: &'a Canvas) -> &'a Canvas {
+        self.render(&mut CanvasRenderer::new(ctx, self.camera.viewport()));
         ctx
     }
 
+    /// Draw the universe as seen by the camera with any
+    /// [`Renderer`](../render/trait.Renderer.html), from its trails and overlays down to the
+    /// legend.
+    pub fn render(&self, r: &mut impl Renderer) {
+        r.clear();
+        let planets: Vec<_> = self.planets.iter().map(|p| p.borrow()).collect();
+        let palette = Palette::new(self.style, planets.iter().map(|p| &**p));
+
+        if self.field.show {
+            self.draw_field(r);
+        }
+
+        if self.trails.length > 0 {
+            for p in planets.iter() {
+                self.draw_trail(r, &p.trail(), palette.color(p));
+            }
+        }
+
+        if !self.particles.is_empty() {
+            self.draw_particles(r);
+        }
+
+        if self.show_quad_tree {
+            self.draw_quad_tree(r);
+        }
+
+        for p in planets.iter() {
+            self.draw_planet(r, p, palette.color(p));
+        }
+
+        if self.overlays.accelerations {
+            for p in planets.iter() {
+                self.draw_acceleration(r, p);
+            }
+        }
+        if self.overlays.velocities {
+            for p in planets.iter() {
+                self.draw_velocity(r, p);
+            }
+        }
+
+        if let Some(selected) = self.selected() {
+            self.draw_selection(r, &selected.borrow());
+        }
+
+        if let Some((start, end)) = self.launch_preview {
+            self.draw_launch_preview(r, start, end);
+        }
+
+        if self.style.color_by != ColorBy::Body {
+            self.draw_legend(r, &palette);
+        }
+    }
+
+    /// Draw the gravitational field as a heatmap, contour lines or both, as configured.
+    fn draw_field(&self, r: &mut impl Renderer) {
+        let cfg = self.field;
+        let grid = self.sample_field(&cfg);
+        let (min, max) = match grid.range() {
+            Some(range) => range,
+            None => return,
+        };
+        let t = |v: f64| {
+            if max > min {
+                (v - min) / (max - min)
+            } else {
+                0.5
+            }
+        };
+
+        if cfg.style.heatmap() {
+            let cell = grid.cell();
+            for row in 0..grid.rows() {
+                for column in 0..grid.columns() {
+                    let pos = Point::new(column as f64 * cell, row as f64 * cell);
+                    let color = cfg.map.at(t(grid.value(column, row)));
+                    // Overlap neighbours a little, so no seams show between cells.
+                    r.fill_rect(pos, cell + 0.5, cell + 0.5, color);
+                }
+            }
+        }
+
+        if cfg.style.contours() {
+            r.set_alpha(if cfg.style.heatmap() { 0.5 } else { 0.8 });
+            for i in 0..cfg.contours {
+                let level = min + (max - min) * (i as f64 + 0.5) / cfg.contours as f64;
+                let color = if cfg.style.heatmap() {
+                    Color::WHITE
+                } else {
+                    cfg.map.at(t(level))
+                };
+                for segment in grid.contour(level) {
+                    r.stroke_line(&segment, 1.0, color);
+                }
+            }
+            r.set_alpha(1.0);
+        }
+    }
+
+    /// Draw the streamlines of the test particles, if they're recorded, and a dot for every
+    /// particle on top of them.
+    fn draw_particles(&self, r: &mut impl Renderer) {
+        for trail in self.particles.trails() {
+            self.draw_trail(r, trail, Color::DODGER_BLUE);
+        }
+
+        r.set_alpha(0.7);
+        for pos in self.particles.positions() {
+            let pos = self.camera.world_to_screen(*pos);
+            r.fill_circle(pos, 1.0, Color::WHITE);
+        }
+        r.set_alpha(1.0);
+    }
+
+    /// Draw a trail as polylines fading out towards the oldest positions. To save on strokes,
+    /// positions of similar age are drawn as one line sharing the same opacity.
+    fn draw_trail(&self, r: &mut impl Renderer, trail: &Trail, color: Color) {
+        let len = trail.len();
+        let step = (len / TRAIL_BANDS).max(1);
+
+        for segment in trail.segments(self.dimensions) {
+            let mut start = 0;
+            while start + 1 < segment.len() {
+                let end = (start + step).min(segment.len() - 1);
+                let age = 1.0 - segment[start].0 as f64 / len as f64;
+                r.set_alpha(self.trails.alpha(age));
+
+                let points: Vec<Point> = segment[start..=end]
+                    .iter()
+                    .map(|(_, p)| self.camera.world_to_screen(*p))
+                    .collect();
+                r.stroke_line(&points, 1.0, color);
+                start = end;
+            }
+        }
+        r.set_alpha(1.0);
+    }
+
+    /// Draw the bounds of every node in the quad tree and mark the centers of mass of internal
+    /// nodes, sized by their share of the total mass. For the selected planet, nodes are
+    /// highlighted according to how they're treated when summing up its forces: approximated
+    /// nodes are filled and linked to the planet, opened ones outlined in orange and those
+    /// whose bodies are applied directly outlined in blue.
+    fn draw_quad_tree(&self, r: &mut impl Renderer) {
+        let qtree = self.build_quad_tree();
+        let total_mass = qtree.mass();
+
+        r.set_alpha(0.4);
+        for node in qtree.iter() {
+            r.stroke_polygon(&self.outline(node.rect), 0.5, Color::CYAN);
+        }
+
+        r.set_alpha(0.8);
+        for node in qtree.iter().filter(|n| n.internal) {
+            let com = self.camera.world_to_screen(node.com);
+            let radius = 2.0 + 8.0 * (node.mass / total_mass).sqrt();
+            r.stroke_circle(com, radius, 1.0, Color::CYAN);
+        }
+
+        if let Some(selected) = self.selected() {
+            let selected = selected.borrow();
+            let pos = self.camera.world_to_screen(selected.pos());
+            for (node, interaction) in qtree.interactions(selected.pos(), selected.id()) {
+                let outline = self.outline(node.rect);
+                match interaction {
+                    Interaction::Approximated => {
+                        r.set_alpha(0.15);
+                        r.fill_polygon(&outline, Color::LIME);
+                        let com = self.camera.world_to_screen(node.com);
+                        r.set_alpha(0.6);
+                        r.stroke_line(&[pos, com], 1.0, Color::LIME);
+                    }
+                    Interaction::Opened => {
+                        r.set_alpha(0.8);
+                        r.stroke_polygon(&outline, 1.0, Color::ORANGE);
+                    }
+                    Interaction::Direct => {
+                        r.set_alpha(0.8);
+                        r.stroke_polygon(&outline, 1.0, Color::DODGER_BLUE);
+                    }
+                }
+            }
+        }
+
+        r.set_alpha(1.0);
+    }
+
+    /// The corners of a rectangle in world coordinates on the screen.
+    fn outline(&self, rect: &Rect) -> [Point; 4] {
+        let corners = [Cardinal::NW, Cardinal::NE, Cardinal::SE, Cardinal::SW];
+        corners.map(|c| self.camera.world_to_screen(rect.corner(c)))
+    }
+
+    /// Draw an arrow from a planet to where it'll be in
+    /// [`velocity_scale`](../overlay/struct.OverlayConfig.html#structfield.velocity_scale) ticks
+    /// at its current velocity. By default, that's the length it would have been dragged to
+    /// launch it.
+    fn draw_velocity(&self, r: &mut impl Renderer, planet: &Planet) {
+        let travel = self
+            .overlays
+            .velocity_arrow(Newtonian::velocity(planet), self.dt);
+        let from = self.camera.world_to_screen(planet.pos());
+        let to = self.camera.world_to_screen(planet.pos() + travel);
+        r.set_alpha(0.7);
+        draw_arrow(r, from, to, Color::WHITE);
+        r.set_alpha(1.0);
+    }
+
+    /// Draw an arrow from a planet in the direction it was last pulled, scaled by
+    /// [`acceleration_scale`](../overlay/struct.OverlayConfig.html#structfield.acceleration_scale).
+    fn draw_acceleration(&self, r: &mut impl Renderer, planet: &Planet) {
+        let pull = self
+            .overlays
+            .acceleration_arrow(planet.acceleration(), self.dt);
+        let from = self.camera.world_to_screen(planet.pos());
+        let to = self.camera.world_to_screen(planet.pos() + pull);
+        r.set_alpha(0.7);
+        draw_arrow(r, from, to, Color::MAGENTA);
+        r.set_alpha(1.0);
+    }
+
+    /// Draw a ring around the selected planet.
+    fn draw_selection(&self, r: &mut impl Renderer, planet: &Planet) {
+        let pos = self.camera.world_to_screen(planet.pos());
+        let radius = self.camera.to_pixels(planet.radius()).max(1.0) + 4.0;
+        r.stroke_circle(pos, radius, 1.0, Color::WHITE);
+    }
+
+    fn draw_launch_preview(&self, r: &mut impl Renderer, start: Point, end: Point) {
+        let start = self.camera.world_to_screen(start);
+        let end = self.camera.world_to_screen(end);
+        r.stroke_line(&[start, end], 1.0, Color::WHITE);
+        r.stroke_circle(start, 3.0, 1.0, Color::WHITE);
+    }
+
+    /// Construct a quad tree holding all living planets.
+    pub fn build_quad_tree(&self) -> QuadNode {
+        let mut qtree = self.init_quad_tree();
+        for planet in self.planets.iter().filter(|p| !p.borrow().dead()) {
+            qtree.insert(planet.clone()).unwrap();
+        }
+        qtree
+    }
+
     fn init_quad_tree(&self) -> QuadNode {
         let width = self.dimensions.0;
         let height = self.dimensions.1;
@@ -141,30 +1270,545 @@ impl Universe {
 
         let cfg = Rc::new(QuadConfig {
             capacity: 1,
-            theta: 0.5,
+            theta: self.theta,
+            softening: self.softening,
         });
 
         QuadNode::new(cfg, bounds)
     }
 
-    fn refresh_canvas<'a>(&self, ctx: &'a Canvas) -> &'a Canvas {
-        ctx.clear_rect(0.0, 0.0, self.dimensions.0, self.dimensions.1);
-        ctx.set_stroke_style(&"magenta".into());
-        ctx.set_fill_style(&"black".into());
-        ctx.set_line_width(4.0);
-        ctx
+    /// Draw a planet filled with `color`. Suns get a glow around them to stand out.
+    fn draw_planet(&self, r: &mut impl Renderer, planet: &Planet, color: Color) {
+        let pos = self.camera.world_to_screen(planet.pos());
+        // Keep bodies visible even if they're far smaller than a pixel at the current scale.
+        let radius = self.camera.to_pixels(planet.radius()).max(1.0);
+
+        if planet.is_sun() {
+            for (scale, alpha) in &[(2.0, 0.1), (1.5, 0.2)] {
+                r.set_alpha(*alpha);
+                r.fill_circle(pos, radius * scale, color);
+            }
+            r.set_alpha(1.0);
+        }
+
+        r.fill_circle(pos, radius, color);
+        r.stroke_circle(pos, radius, 2.0, color.lerp(Color::WHITE, 0.5));
     }
 
-    fn draw_planet<'a>(&self, ctx: &'a Canvas, planet: Rc<RefCell<Planet>>) -> &'a Canvas {
-        let planet = planet.borrow();
-        let pos = planet.pos();
-        ctx.begin_path();
-        ctx.arc(pos.x, pos.y, planet.radius(), 0.0, PI * 2.0)
-            .unwrap();
-        ctx.stroke();
-        ctx.fill();
-        ctx.set_stroke_style(&"white".into());
-        ctx.set_fill_style(&"gray".into());
-        ctx
+    /// Draw a gradient bar for the colour map in the bottom left corner, labelled with the
+    /// property and its range.
+    fn draw_legend(&self, r: &mut impl Renderer, palette: &Palette) {
+        let (_, height) = self.camera.viewport();
+        let (x, y) = (10.0, height - 30.0);
+        let (width, bar_height) = (160.0, 10.0);
+        let cfg = palette.config();
+
+        let steps = 32;
+        for i in 0..steps {
+            let t = i as f64 / (steps - 1) as f64;
+            let step_width = width / steps as f64;
+            let pos = Point::new(x + i as f64 * step_width, y);
+            r.fill_rect(pos, step_width + 0.5, bar_height, cfg.map.at(t));
+        }
+
+        let scale = if cfg.color_by.logarithmic() {
+            " (log)"
+        } else {
+            ""
+        };
+        let label = format!("{}{}", cfg.color_by.label(), scale);
+        r.fill_text(&label, Point::new(x, y - 6.0), Color::WHITE);
+        if let Some((min, max)) = palette.range() {
+            let below = y + bar_height + 14.0;
+            r.fill_text(&format!("{:.3e}", min), Point::new(x, below), Color::WHITE);
+            let max_pos = Point::new(x + width - 50.0, below);
+            r.fill_text(&format!("{:.3e}", max), max_pos, Color::WHITE);
+        }
+    }
+}
+
+/// The pull on every planet and test particle, along with where they were when it was worked out.
+/// The pulls only hold as long as nothing was moved, added or removed since.
+struct Pulls {
+    /// Id, position and mass of every planet, in the order of the universe's planets.
+    bodies: Vec<(Uuid, Point, f64)>,
+    /// Positions of the test particles.
+    positions: Vec<Point>,
+    /// Acceleration of every planet.
+    planets: Vec<Point>,
+    /// Acceleration of every test particle.
+    particles: Vec<Point>,
+}
+
+impl Pulls {
+    fn bodies(planets: &[Rc<RefCell<Planet>>]) -> Vec<(Uuid, Point, f64)> {
+        planets
+            .iter()
+            .map(|p| {
+                let p = p.borrow();
+                (p.id(), p.pos(), p.mass())
+            })
+            .collect()
+    }
+
+    /// Whether the pulls still hold for the planets and test particles of `universe`.
+    fn hold_for(&self, universe: &Universe) -> bool {
+        self.positions == universe.particles.positions()
+            && self.bodies == Self::bodies(&universe.planets)
+    }
+}
+
+/// Draw a line from `from` to `to` with an arrowhead at `to`, sized to the length of the line
+/// but at most [`ARROW_HEAD`](../constant.ARROW_HEAD.html) pixels.
+fn draw_arrow(r: &mut impl Renderer, from: Point, to: Point, color: Color) {
+    let d = to - from;
+    let length = d.mag();
+    if length < 1.0 {
+        return;
+    }
+    let head = ARROW_HEAD.min(length / 3.0);
+    let back = d * (head / length);
+    let side = Point::new(-back.y, back.x) * 0.5;
+    r.stroke_line(&[from, to], 1.0, color);
+    r.stroke_line(&[to - back + side, to, to - back - side], 1.0, color);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spawn_circular() {
+        let mut universe = Universe::new((800.0, 600.0));
+        let center = Point::new(400.0, 300.0);
+        universe.add(Planet::new_sun(center.x, center.y));
+
+        // Light enough not to disturb each other.
+        let cfg = SpawnConfig {
+            mass: 1_000.0,
+            ..SpawnConfig::default()
+        };
+        universe.spawn_planet(Point::new(500.0, 300.0), &cfg);
+        let cfg = SpawnConfig {
+            retrograde: true,
+            ..cfg
+        };
+        universe.spawn_planet(Point::new(250.0, 300.0), &cfg);
+
+        let prograde = universe.planets()[1].clone();
+        let retrograde = universe.planets()[2].clone();
+        assert!(Newtonian::velocity(&*prograde.borrow()).y > 0.0);
+        assert!(Newtonian::velocity(&*retrograde.borrow()).y > 0.0);
+
+        for _ in 0..1_000 {
+            universe.tick();
+            let r = prograde.borrow().pos().distance_to(center);
+            assert!((r - 100.0).abs() < 3.0, "{}", r);
+            let r = retrograde.borrow().pos().distance_to(center);
+            assert!((r - 150.0).abs() < 4.5, "{}", r);
+        }
+    }
+
+    #[test]
+    fn inspect_pin_remove() {
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.add(Planet::new_sun(400.0, 300.0));
+        let cfg = SpawnConfig {
+            mass: 1_000.0,
+            ..SpawnConfig::default()
+        };
+        universe.spawn_planet(Point::new(500.0, 300.0), &cfg);
+        let planet = universe.planets()[1].clone();
+        let id = planet.borrow().id();
+        universe.select(Some(id));
+
+        let sun_mass = universe.sun().unwrap().borrow().mass();
+        let inspection = universe.inspect(&planet.borrow());
+        let expected = universe.units().g() * sun_mass * cfg.mass / (100.0 * 100.0);
+        assert!((inspection.net_force.mag() - expected).abs() / expected < 1e-9);
+        assert!(inspection.net_force.x < 0.0);
+        let (orbit, _) = inspection.orbit.unwrap();
+        assert!(orbit.eccentricity < 1e-6);
+        assert!((orbit.semi_major_axis - 100.0).abs() < 1e-6);
+
+        planet.borrow().pin(true);
+        for _ in 0..10 {
+            universe.tick();
+        }
+        assert_eq!(planet.borrow().pos(), Point::new(500.0, 300.0));
+
+        universe.remove(id);
+        assert_eq!(universe.planets().len(), 1);
+        assert!(universe.selected().is_none());
+    }
+
+    #[test]
+    fn planet_at() {
+        let mut universe = Universe::new((800.0, 600.0));
+        let big = universe.add(Planet::new(400.0, 300.0, 1.0, 50.0, Point::default()));
+        let small = universe.add(Planet::new(460.0, 300.0, 1.0, 20.0, Point::default()));
+        let tiny = universe.add(Planet::new(100.0, 100.0, 1.0, 0.1, Point::default()));
+        let id = |pos, tolerance| universe.planet_at(pos, tolerance).map(|p| p.borrow().id());
+
+        assert_eq!(id(Point::new(380.0, 300.0), 0.0), Some(big));
+        // Of overlapping planets, the one whose edge is furthest away wins.
+        assert_eq!(id(Point::new(448.0, 300.0), 0.0), Some(small));
+        assert_eq!(id(Point::new(444.0, 300.0), 0.0), Some(big));
+        assert_eq!(id(Point::new(102.0, 100.0), 0.0), None);
+        assert_eq!(id(Point::new(102.0, 100.0), 3.0), Some(tiny));
+        assert_eq!(id(Point::new(700.0, 500.0), 3.0), None);
+    }
+
+    #[test]
+    fn events() {
+        let mut universe = Universe::new((800.0, 600.0));
+        let sun = universe.add(Planet::new_sun(400.0, 300.0));
+        let v = Point::new(0.0, 0.0);
+        let meal = universe.add(Planet::new(405.0, 300.0, 5_000.0, 5.0, v));
+        universe.set_boundary(Boundary::Open);
+        let runaway = universe.add(Planet::new(
+            799.5,
+            300.0,
+            5_000.0,
+            1.0,
+            Point::new(1.0, 0.0),
+        ));
+
+        let events: Vec<Event> = universe.drain_events().collect();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[1].kind(), "spawn");
+        assert_eq!(events[1].id(), meal);
+
+        universe.tick();
+        let events: Vec<Event> = universe.drain_events().collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind(), "escape");
+        assert_eq!(events[0].id(), runaway);
+        match events[1] {
+            Event::Merge { id, eaten, .. } => assert_eq!((id, eaten), (sun, meal)),
+            e => panic!("Expected a merge, got {:?}", e),
+        }
+        // Whatever happened during the tick is stamped with the time at its end.
+        assert!(events.iter().all(|e| e.time() == universe.time()));
+        assert_eq!(universe.planets().len(), 1);
+        assert_eq!(universe.drain_events().count(), 0);
+
+        universe.remove(sun);
+        assert_eq!(universe.drain_events().next().unwrap().kind(), "remove");
+    }
+
+    #[test]
+    fn eaten_once() {
+        // A small planet inside two suns only gets eaten by the first of them.
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.add(Planet::new_sun(400.0, 300.0));
+        universe.add(Planet::new(425.0, 300.0, 6_000.0, 6.0, Point::default()));
+        universe.add(Planet::new_sun(450.0, 300.0));
+        let mass = |universe: &Universe| -> f64 {
+            universe.planets().iter().map(|p| p.borrow().mass()).sum()
+        };
+        let before = mass(&universe);
+        universe.drain_events().for_each(drop);
+
+        universe.tick();
+        let merges = universe
+            .drain_events()
+            .filter(|e| e.kind() == "merge")
+            .count();
+        assert_eq!(merges, 1);
+        assert_eq!(universe.planets().len(), 2);
+        assert!((mass(&universe) - before).abs() < 1e-9 * before);
+    }
+
+    #[test]
+    fn coincident_planets() {
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.add(Planet::new(400.0, 300.0, 6_000.0, 6.0, Point::default()));
+        universe.add(Planet::new(400.0, 300.0, 6_000.0, 6.0, Point::default()));
+        universe.add(Planet::new(450.0, 300.0, 6_000.0, 6.0, Point::default()));
+
+        let (kinetic, potential) = universe.energy();
+        assert_eq!(kinetic, 0.0);
+        assert!(potential.is_finite() && potential < 0.0);
+        for p in universe.planets() {
+            let force = universe.net_force(&p.borrow());
+            assert!(force.x.is_finite() && force.y.is_finite());
+        }
+    }
+
+    #[test]
+    fn tunneling() {
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.set_collisions(CollisionModel::Elastic { restitution: 1.0 });
+        let v = Point::new(0.0, 0.0);
+        let target = universe.add(Planet::new(400.0, 300.0, 1.0, 2.0, v));
+        // Fast enough to skip from one side of the target to the other within a tick.
+        let bullet = universe.add(Planet::new(380.0, 300.0, 1.0, 2.0, Point::new(40.0, 0.0)));
+
+        universe.tick();
+        let velocity = |id| {
+            let p = universe.planets().iter().find(|p| p.borrow().id() == id);
+            Newtonian::velocity(&*p.unwrap().borrow())
+        };
+        assert!(velocity(bullet).x < 1e-6);
+        assert!(velocity(target).x > 39.0);
+
+        // Far apart planets don't collide, no matter how big the others are.
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.add(Planet::new(100.0, 100.0, 1.0, 50.0, v));
+        universe.add(Planet::new(300.0, 300.0, 1.0, 2.0, v));
+        universe.add(Planet::new(306.0, 300.0, 1.0, 2.0, v));
+        universe.tick();
+        assert_eq!(universe.planets().len(), 3);
+    }
+
+    #[test]
+    fn bounce_apart() {
+        // After bouncing off each other, planets still touching at the start of a tick move on.
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.set_collisions(CollisionModel::Elastic { restitution: 1.0 });
+        let a = universe.add(Planet::new(300.0, 300.0, 1e-6, 2.0, Point::new(1.0, 0.0)));
+        let b = universe.add(Planet::new(320.0, 300.0, 1e-6, 3.0, Point::new(-1.3, 0.0)));
+        let x = |universe: &Universe, id| {
+            let p = universe.planets().iter().find(|p| p.borrow().id() == id);
+            p.unwrap().borrow().pos().x
+        };
+
+        for _ in 0..10 {
+            universe.tick();
+        }
+        let gap = x(&universe, b) - x(&universe, a);
+        for _ in 0..10 {
+            let (xa, xb) = (x(&universe, a), x(&universe, b));
+            universe.tick();
+            assert!(x(&universe, a) < xa && x(&universe, b) < xb);
+        }
+        assert!(x(&universe, b) - x(&universe, a) > gap + 20.0);
+    }
+
+    #[test]
+    fn solvers_and_integrators() {
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.init_random();
+
+        // Without approximations, the tree pulls just like summing up every planet.
+        let direct = universe.accelerations();
+        universe.set_solver(Solver::BarnesHut);
+        universe.set_theta(0.0);
+        for (a, b) in direct.iter().zip(universe.accelerations()) {
+            assert!(a.distance_to(b) <= 1e-9 * a.mag());
+        }
+        // Approximations may be off for planets whose pulls almost cancel out, but not overall.
+        universe.set_theta(0.5);
+        let error: f64 = direct
+            .iter()
+            .zip(universe.accelerations())
+            .map(|(a, b)| a.distance_to(b))
+            .sum();
+        let total: f64 = direct.iter().map(|a| a.mag()).sum();
+        assert!(error <= 0.05 * total);
+
+        // Leapfrog keeps the energy of orbits much closer to where it started.
+        let drift = |integrator| {
+            let mut universe = scenario::solar_system((800.0, 600.0), false);
+            universe.set_integrator(integrator);
+            let (kinetic, potential) = universe.energy();
+            let initial = kinetic + potential;
+            for _ in 0..1000 {
+                universe.tick();
+            }
+            let (kinetic, potential) = universe.energy();
+            ((kinetic + potential - initial) / initial).abs()
+        };
+        let euler = drift(Integrator::Euler);
+        let leapfrog = drift(Integrator::Leapfrog);
+        assert!(leapfrog < euler / 10.0);
+        assert!(leapfrog < 1e-6);
+    }
+
+    #[test]
+    fn pulls_are_kept() {
+        // A universe starting from the pulls of its last tick carries on just like one working
+        // them out afresh, also once a planet was moved in between.
+        let mut universe = scenario::solar_system((800.0, 600.0), false);
+        universe.tick();
+        let mut fresh = Universe::new((800.0, 600.0));
+        fresh.restore(&universe.snapshot());
+
+        universe.tick();
+        fresh.tick();
+        assert_eq!(fresh.snapshot(), universe.snapshot());
+
+        for u in [&mut universe, &mut fresh].iter_mut() {
+            let planet = u.planets()[1].clone();
+            let pos = planet.borrow().pos();
+            planet.borrow().set_pos(pos + Point::new(10.0, 0.0));
+        }
+        fresh.pulls = None;
+        universe.tick();
+        fresh.tick();
+        assert_eq!(fresh.snapshot(), universe.snapshot());
+    }
+
+    #[test]
+    fn render() {
+        let mut universe = Universe::new((80.0, 60.0));
+        universe.add(Planet::new_sun(40.0, 30.0));
+        let mut raster = Raster::new(80, 60);
+        universe.render(&mut raster);
+
+        let sun = raster.pixel(40, 30);
+        assert_eq!(&sun[..3], &[Color::SUN.r, Color::SUN.g, Color::SUN.b]);
+        assert_eq!(raster.pixel(0, 0), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn svg() {
+        let run = || {
+            let mut universe = scenario::solar_system((400.0, 300.0), false);
+            universe.set_trail_config(TrailConfig {
+                length: 10,
+                fade: 1.0,
+            });
+            universe.set_show_quad_tree(true);
+            universe.set_overlays(OverlayConfig {
+                velocities: true,
+                ..OverlayConfig::default()
+            });
+            for _ in 0..20 {
+                universe.tick();
+            }
+            universe
+        };
+
+        // The same run gives the same document, down to the byte.
+        let universe = run();
+        let svg = universe.to_svg();
+        assert_eq!(run().to_svg(), svg);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("<rect x="));
+        // A filled and an outlined circle per planet, plus the glow of the sun.
+        let planets = universe.planets().len();
+        assert!(svg.matches("<circle").count() >= 2 * planets + 2);
+        // A trail and an arrow with its head for every planet but the resting sun.
+        assert!(svg.matches("<path").count() >= 3 * (planets - 1));
+    }
+
+    #[test]
+    fn accelerations_are_kept() {
+        let mut universe = scenario::solar_system((400.0, 300.0), false);
+        let before = universe.accelerations();
+        universe.tick();
+        // Euler kicks with the pull at the start of the tick.
+        for (p, acc) in universe.planets().iter().zip(before) {
+            assert_eq!(p.borrow().acceleration(), acc);
+        }
+
+        // Leapfrog's last kick is worked out where the planets ended up.
+        universe.set_integrator(Integrator::Leapfrog);
+        universe.tick();
+        for (p, acc) in universe.planets().iter().zip(universe.accelerations()) {
+            assert_eq!(p.borrow().acceleration(), acc);
+        }
+
+        let earth = universe.planets()[3].borrow();
+        let sun = universe.planets()[0].borrow();
+        // Pulled towards the sun.
+        let (acc, towards) = (earth.acceleration(), sun.pos() - earth.pos());
+        assert!(acc.x * towards.x + acc.y * towards.y > 0.0);
+    }
+
+    #[test]
+    fn test_particles() {
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.set_integrator(Integrator::Leapfrog);
+        let sun = Point::new(400.0, 300.0);
+        universe.add(Planet::new_sun(sun.x, sun.y));
+
+        // Seeded all over, but not inside the sun.
+        let area = Rect::new(400.0, 300.0, 800.0, 600.0);
+        let seeded = universe.seed_particles(&area, 10.0, false);
+        assert!(seeded > 0 && seeded < 80 * 60);
+        universe.clear_particles();
+
+        // Orbiting particles stay on their orbits, without pulling at anything.
+        universe.spawn_particle(Point::new(500.0, 300.0));
+        universe.add_particle(Point::new(400.0, 250.0), Point::default());
+        universe.set_particle_config(ParticleConfig {
+            absorb: true,
+            streamlines: 10,
+        });
+        for _ in 0..500 {
+            universe.tick();
+        }
+        let planets = universe.planets();
+        assert_eq!(planets.len(), 1);
+        assert_eq!(planets[0].borrow().acceleration(), Point::default());
+
+        // The one falling straight in was absorbed.
+        let particles = universe.particles();
+        assert_eq!(particles.len(), 1);
+        let r = particles.positions()[0].distance_to(sun);
+        assert!((r - 100.0).abs() < 1.0);
+        assert_eq!(particles.trails()[0].len(), 10);
+    }
+
+    #[test]
+    fn carry_settings() {
+        let mut old = Universe::new((800.0, 600.0));
+        old.init_random();
+        old.set_dt(0.5);
+        old.set_solver(Solver::Direct);
+        old.set_integrator(Integrator::Leapfrog);
+        old.set_boundary(Boundary::Open);
+        old.set_theta(0.9);
+        old.set_softening(2.0);
+        old.set_collisions(CollisionModel::Elastic { restitution: 0.5 });
+        old.set_particle_config(ParticleConfig {
+            absorb: true,
+            streamlines: 10,
+        });
+        let mut trails = old.trail_config();
+        trails.length = 7;
+        old.set_trail_config(trails);
+        let mut style = old.style();
+        style.color_by = ColorBy::Speed;
+        old.set_style(style);
+        old.set_show_quad_tree(true);
+        let mut overlays = old.overlays();
+        overlays.velocities = true;
+        old.set_overlays(overlays);
+        let mut field = old.field();
+        field.show = true;
+        old.set_field(field);
+
+        let mut universe = Universe::new((800.0, 600.0));
+        universe.carry_settings(&old);
+        assert!(universe.planets().is_empty());
+        assert_eq!(universe.dt(), 0.5);
+        assert_eq!(universe.solver(), Solver::Direct);
+        assert_eq!(universe.integrator(), Integrator::Leapfrog);
+        assert_eq!(universe.boundary(), Boundary::Open);
+        assert_eq!(universe.theta(), 0.9);
+        assert_eq!(universe.softening(), 2.0);
+        assert_eq!(universe.collisions(), old.collisions());
+        assert_eq!(universe.particle_config(), old.particle_config());
+        assert_eq!(universe.trail_config(), trails);
+        assert_eq!(universe.style(), style);
+        assert!(universe.show_quad_tree());
+        assert_eq!(universe.overlays(), overlays);
+        assert_eq!(universe.field(), field);
+
+        // A time step in other units would mean something else.
+        let mut galaxy = Universe::with_units(
+            (40.0, 30.0),
+            UnitSystem::astronomical(),
+            0.01,
+            (800.0, 600.0),
+        );
+        galaxy.carry_settings(&old);
+        assert_eq!(galaxy.dt(), 0.01);
+        assert_eq!(galaxy.solver(), Solver::Direct);
     }
 }